@@ -0,0 +1,205 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::plant::genome::PlantGenome;
+use crate::world::{World, WorldClock};
+
+pub const DEFAULT_SNAPSHOT_PATH: &str = "world.pesnap";
+
+pub struct HeadlessOptions {
+    pub seed: Option<u64>,
+    pub ticks: u64,
+    pub report_every: u64,
+    pub initial_plants: usize,
+    pub initial_herbivores: usize,
+    pub spawn_genome: PlantGenome,
+    pub clock: WorldClock,
+
+    pub snapshot_path: String,
+    pub load: bool,
+    pub save: bool,
+    pub checkpoint_every: u64
+}
+
+impl HeadlessOptions {
+    pub fn from_args(args: &[String]) -> HeadlessOptions {
+        let mut options = HeadlessOptions {
+            seed: None,
+            ticks: 100_000,
+            report_every: 1_000,
+            initial_plants: 100,
+            initial_herbivores: 10,
+            spawn_genome: spawn_genome_from_args(args),
+            clock: world_clock_from_args(args),
+
+            snapshot_path: snapshot_path_from_args(args),
+            load: false,
+            save: false,
+            checkpoint_every: 0
+        };
+
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--seed" => {
+                    options.seed = Some(parse_arg_value(args, i));
+                    i += 1;
+                },
+                "--ticks" => {
+                    options.ticks = parse_arg_value(args, i);
+                    i += 1;
+                },
+                "--report-every" => {
+                    options.report_every = parse_arg_value(args, i);
+                    i += 1;
+                },
+                "--initial-plants" => {
+                    options.initial_plants = parse_arg_value(args, i);
+                    i += 1;
+                },
+                "--initial-herbivores" => {
+                    options.initial_herbivores = parse_arg_value(args, i);
+                    i += 1;
+                },
+                "--checkpoint-every" => {
+                    options.checkpoint_every = parse_arg_value(args, i);
+                    i += 1;
+                },
+                "--load" => {
+                    options.load = true;
+                },
+                "--save" => {
+                    options.save = true;
+                },
+                _ => ()
+            }
+            i += 1;
+        }
+
+        return options;
+    }
+}
+
+pub fn world_seed_from_args(args: &[String]) -> u64 {
+    //Uses --seed if it was given, otherwise picks one from the clock
+    match args.iter().position(|arg| arg == "--seed") {
+        Some(i) => parse_arg_value(args, i),
+        None => random_world_seed()
+    }
+}
+
+pub fn random_world_seed() -> u64 {
+    //Printed so that the run can be repeated later with --seed
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_nanos() as u64).unwrap_or(0);
+    println!("World seed: {}", seed);
+    return seed;
+}
+
+pub fn world_clock_from_args(args: &[String]) -> WorldClock {
+    //Only used for new worlds, snapshots carry their own clock
+    let mut clock = WorldClock::new();
+    match args.iter().position(|arg| arg == "--day-length") {
+        Some(i) => clock.day_length_ticks = parse_arg_value(args, i),
+        None => ()
+    }
+    match args.iter().position(|arg| arg == "--year-length") {
+        Some(i) => clock.year_length_days = parse_arg_value(args, i),
+        None => ()
+    }
+    match args.iter().position(|arg| arg == "--latitude") {
+        Some(i) => clock.latitude = parse_arg_value(args, i),
+        None => ()
+    }
+    return clock;
+}
+
+pub fn spawn_genome_from_args(args: &[String]) -> PlantGenome {
+    //Uses the genome file given with --genome for newly spawned plants, otherwise the built in tree
+    match args.iter().position(|arg| arg == "--genome") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => match PlantGenome::load_from_file(path) {
+                Ok(genome) => genome,
+                Err(error) => panic!("Unable to load genome {}", error)
+            },
+            None => panic!("Expected a path after --genome")
+        },
+        None => PlantGenome::human_made_tree_genome()
+    }
+}
+
+pub fn snapshot_path_from_args(args: &[String]) -> String {
+    match args.iter().position(|arg| arg == "--snapshot") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => path.clone(),
+            None => panic!("Expected a path after --snapshot")
+        },
+        None => DEFAULT_SNAPSHOT_PATH.to_string()
+    }
+}
+
+fn parse_arg_value<T: std::str::FromStr>(args: &[String], flag_index: usize) -> T {
+    match args.get(flag_index + 1).and_then(|val| val.parse().ok()) {
+        Some(val) => val,
+        None => panic!("Expected a number after {}", args[flag_index])
+    }
+}
+
+pub fn run_headless(options: HeadlessOptions) {
+    // Same simulation as the windowed mode, minus the camera and every GPU upload, running as fast as the CPU allows
+    let mut world: World;
+    if options.load {
+        world = World::load_snapshot(&options.snapshot_path).expect("Unable to load world snapshot");
+        println!("Resuming from tick {} of {}", world.total_ticks, options.snapshot_path);
+    } else {
+        world = World::world_init(options.seed.unwrap_or_else(random_world_seed));
+        world.clock = options.clock;
+        world.spawn_random_plants(options.initial_plants, &options.spawn_genome);
+        world.spawn_random_herbivores(options.initial_herbivores);
+    }
+
+    let start_time = Instant::now();
+    let start_tick = world.total_ticks;
+    let end_tick = start_tick + options.ticks;
+
+    while world.total_ticks < end_tick {
+        world.tick(None);
+
+        if options.report_every != 0 && world.total_ticks.is_multiple_of(options.report_every) {
+            print_report(&world, world.total_ticks - start_tick, start_time);
+        }
+        if options.checkpoint_every != 0 && world.total_ticks.is_multiple_of(options.checkpoint_every) {
+            save(&world, &options.snapshot_path);
+        }
+    }
+
+    print_report(&world, world.total_ticks - start_tick, start_time);
+
+    if options.save {
+        save(&world, &options.snapshot_path);
+    }
+}
+
+fn save(world: &World, path: &str) {
+    match world.save_snapshot(path) {
+        Ok(()) => println!("Saved world at tick {} to {}", world.total_ticks, path),
+        Err(error) => println!("Unable to save world to {}: {}", path, error)
+    }
+}
+
+fn print_report(world: &World, ticks_this_run: u64, start_time: Instant) {
+    let living_plants = world.plants.internal_vec.iter().filter(|item| item.is_some()).count();
+    let elapsed = start_time.elapsed().as_secs_f64();
+
+    println!(
+        "Tick {} (day {}, season {:.2}): {} plants, {} seeds, {} herbivores ({:.1} ticks/s)", 
+        world.total_ticks, 
+        world.clock.day(world.total_ticks),
+        world.clock.season(world.total_ticks),
+        living_plants, 
+        world.plants.seeds.len(), 
+        world.herbivores.herbivores.len(),
+        ticks_this_run as f64 / elapsed.max(0.000_001)
+    );
+
+    let death_counts: Vec<String> = world.plants.death_counts().iter().map(|(cause, count)| format!("{} {}", count, cause.name())).collect();
+    println!("    Last {} deaths: {}", world.plants.death_records.len(), death_counts.join(", "));
+}
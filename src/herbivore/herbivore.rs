@@ -0,0 +1,124 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::plant::Plant;
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+use crate::terrain::nutrients::NITROGEN_PER_COST;
+use crate::world::WorldRng;
+
+pub const HERBIVORE_SPEED: f32 = 0.15;              // Terrain cells moved per herbivore tick
+pub const HERBIVORE_SIGHT: f32 = 8.0;               // In terrain cells
+pub const HERBIVORE_REACH: f32 = 0.4;               // How close (in terrain cells) a herbivore has to be to a plant to graze on it
+pub const HERBIVORE_METABOLISM: f32 = 0.07;         // Energy burnt per herbivore tick
+pub const HERBIVORE_BITE_CHANCE: f32 = 0.05;        // Chance per herbivore tick of taking a bite when next to a plant, chewing takes a while
+pub const HERBIVORE_DIGESTION: f32 = 0.5;           // Fraction of an eaten branch's cost the herbivore gets as energy
+pub const HERBIVORE_LEAF_DIGESTION: f32 = 1.0;      // Same for leaves, which are soft enough to be digested whole
+pub const HERBIVORE_FULL_ENERGY: f32 = 200.0;       // Herbivores with more energy than this don't bother eating
+pub const HERBIVORE_BIRTH_ENERGY: f32 = 150.0;      // Herbivores with more energy than this give birth
+pub const HERBIVORE_LIFESPAN: u64 = 3000;           // In herbivore ticks
+pub const HERBIVORE_BABY_ENERGY: f32 = 60.0;
+pub const HERBIVORE_MIN_PLANT_AGE: u64 = 300;       // Seedlings younger than this (in plant ticks) are too small for herbivores to notice
+
+pub struct Herbivore {
+    pub x: f32,                 // In terrain cells
+    pub z: f32,
+    pub heading: f32,           // Radians, 0 faces +x
+    pub energy: f32,
+    pub age_ticks: u64          // In herbivore ticks
+}
+
+impl Herbivore {
+    pub fn new(x: f32, z: f32, energy: f32, rng: &mut WorldRng) -> Herbivore {
+        return Herbivore {
+            x,
+            z,
+            heading: rng.random_range(0.0 .. 2.0 * PI),
+            energy,
+            age_ticks: 0
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        return self.energy < 0.0 || self.age_ticks > HERBIVORE_LIFESPAN;
+    }
+
+    pub fn wander(&mut self, terrain: &Terrain, rng: &mut WorldRng) {
+        self.heading += rng.random_range(-0.5 .. 0.5);
+        self.step(terrain);
+    }
+
+    pub fn move_towards(&mut self, x: f32, z: f32, terrain: &Terrain) {
+        //Takes the short way around the world edges, and stops short of walking past the target
+        let rows = TERRAIN_GRID_ROWS as f32;
+        let x_diff = (x - self.x + rows * 0.5).rem_euclid(rows) - rows * 0.5;
+        let z_diff = (z - self.z + rows * 0.5).rem_euclid(rows) - rows * 0.5;
+
+        self.heading = z_diff.atan2(x_diff);
+        if (x_diff * x_diff + z_diff * z_diff).sqrt() > HERBIVORE_SPEED {
+            self.step(terrain);
+        }
+    }
+
+    fn step(&mut self, terrain: &Terrain) {
+        //Herbivores won't walk into the water, they turn around instead
+        let new_x = (self.x + self.heading.cos() * HERBIVORE_SPEED).rem_euclid(TERRAIN_GRID_ROWS as f32);
+        let new_z = (self.z + self.heading.sin() * HERBIVORE_SPEED).rem_euclid(TERRAIN_GRID_ROWS as f32);
+
+        if terrain.get_height(new_x, new_z) < terrain.water_height {
+            self.heading += PI;
+            return;
+        }
+
+        self.x = new_x;
+        self.z = new_z;
+    }
+
+    pub fn choose_bite(plant: &Plant) -> usize {
+        //The tastiest tip: leafy branches over tough ones. Plants with nothing but a trunk left get the trunk eaten
+        let mut best_index: usize = 0;
+        let mut best_palatability: f32 = f32::MIN;
+
+        for (branch_index, branch) in plant.branches.iter().enumerate().skip(1) {
+            if !branch.offshoots.is_empty() {
+                continue;
+            }
+
+            let palatability = branch.photoreceptiveness - branch.strength;
+            if palatability > best_palatability {
+                best_index = branch_index;
+                best_palatability = palatability;
+            }
+        }
+        return best_index;
+    }
+
+    pub fn graze(&mut self, plant: &mut Plant, terrain: &mut Terrain, rng: &mut WorldRng) -> bool {
+        //Takes one bite out of the plant. Strong branches are tough to get through, so bites on them often fail.
+        //Whatever nitrogen was in the bite goes straight back to the soil as dung. Returns true when the trunk got eaten, killing the plant
+        if rng.random_range(0.0 .. 1.0) > HERBIVORE_BITE_CHANCE {
+            return false;
+        }
+        let branch_index = Self::choose_bite(plant);
+
+        //Leaves are soft, so a bite on a leafy branch always gets through and strips off one of its leaves instead
+        match plant.branches[branch_index].leaves.pop() {
+            Some(leaf) => {
+                self.energy += leaf.calculate_cost() * HERBIVORE_LEAF_DIGESTION;
+                terrain.add_detritus(self.x, self.z, leaf.calculate_cost() * NITROGEN_PER_COST);
+                return false;
+            },
+            None => ()
+        }
+
+        if rng.random_range(0.0 .. 1.0) < plant.branches[branch_index].strength {
+            return false;
+        }
+
+        let eaten_cost = plant.delete_branch_recursive(branch_index);
+        self.energy += eaten_cost * HERBIVORE_DIGESTION;
+        terrain.add_detritus(self.x, self.z, eaten_cost * NITROGEN_PER_COST);
+
+        return branch_index == 0;
+    }
+}
@@ -0,0 +1,114 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::herbivore::herbivore::{Herbivore, HERBIVORE_SIGHT, HERBIVORE_REACH, HERBIVORE_METABOLISM, HERBIVORE_FULL_ENERGY, HERBIVORE_MIN_PLANT_AGE, HERBIVORE_BIRTH_ENERGY, HERBIVORE_BABY_ENERGY};
+use crate::plant::death::DeathCause;
+use crate::plant::plant_option_vec::PlantOptionVec;
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+use crate::terrain::nutrients::NITROGEN_PER_COST;
+use crate::world::{WorldRng, LightGrid};
+
+pub const HERBIVORE_TICK_MOD: u64 = 10;     // Herbivores get updated once every this many world ticks
+pub const MAX_HERBIVORES: usize = 30;
+const HERBIVORE_BODY_COST: f32 = 50.0;      // Dead herbivores rot into as much nitrogen as a branch of this cost
+
+pub struct HerbivoreVec {
+    pub herbivores: Vec<Herbivore>
+}
+
+impl HerbivoreVec {
+    pub fn new() -> HerbivoreVec {
+        return HerbivoreVec {
+            herbivores: vec![]
+        }
+    }
+
+    pub fn tick(&mut self, terrain: &mut Terrain, plants: &mut PlantOptionVec, light_grid: &mut LightGrid, total_ticks: u64, rng: &mut WorldRng) {
+        let mut births: Vec<Herbivore> = vec![];
+        let herbivore_count = self.herbivores.len();
+
+        for herbivore in &mut self.herbivores {
+            herbivore.age_ticks += 1;
+            herbivore.energy -= HERBIVORE_METABOLISM;
+
+            //Full herbivores leave the plants alone and wander off
+            let nearest_plant = match herbivore.energy > HERBIVORE_FULL_ENERGY {
+                true => None,
+                false => Self::find_nearest_plant(plants, herbivore.x, herbivore.z)
+            };
+
+            match nearest_plant {
+                Some((plant_index, dist)) => {
+                    if dist <= HERBIVORE_REACH {
+                        let plant = plants.internal_vec[plant_index].as_mut().unwrap();
+                        if herbivore.graze(plant, terrain, rng) {
+                            plant.die(terrain, light_grid);
+                            plants.remove_plant(plant_index, DeathCause::Grazing, total_ticks);
+                        }
+                    } else {
+                        let plant_position = plants.internal_vec[plant_index].as_ref().unwrap().root_position;
+                        herbivore.move_towards(plant_position.0, plant_position.2, terrain);
+                    }
+                },
+                None => {
+                    herbivore.wander(terrain, rng);
+                }
+            }
+
+            if herbivore.energy > HERBIVORE_BIRTH_ENERGY && herbivore_count + births.len() < MAX_HERBIVORES {
+                herbivore.energy -= HERBIVORE_BABY_ENERGY;
+                let angle = rng.random_range(0.0 .. 2.0 * PI);
+                births.push(Herbivore::new(
+                    (herbivore.x + angle.cos()).rem_euclid(TERRAIN_GRID_ROWS as f32),
+                    (herbivore.z + angle.sin()).rem_euclid(TERRAIN_GRID_ROWS as f32),
+                    HERBIVORE_BABY_ENERGY,
+                    rng
+                ));
+            }
+        }
+
+        //Starved and old herbivores rot where they fell
+        for herbivore in &self.herbivores {
+            if herbivore.is_dead() {
+                terrain.add_detritus(herbivore.x, herbivore.z, HERBIVORE_BODY_COST * NITROGEN_PER_COST);
+            }
+        }
+        self.herbivores.retain(|herbivore| !herbivore.is_dead());
+        self.herbivores.append(&mut births);
+    }
+
+    fn find_nearest_plant(plants: &PlantOptionVec, x: f32, z: f32) -> Option<(usize, f32)> {
+        //Nearest plant in sight that's old enough to be worth eating. Ties go to the lower plant index
+        let mut nearest: Option<(usize, f32)> = None;
+        for (plant_index, dist) in plants.spatial_index.query_radius(x, z, HERBIVORE_SIGHT) {
+            if plants.internal_vec[plant_index].as_ref().unwrap().age_ticks < HERBIVORE_MIN_PLANT_AGE {
+                continue;
+            }
+            match nearest {
+                Some((_, nearest_dist)) => {
+                    if dist < nearest_dist {
+                        nearest = Some((plant_index, dist));
+                    }
+                },
+                None => {
+                    nearest = Some((plant_index, dist));
+                }
+            }
+        }
+        return nearest;
+    }
+
+    pub fn spawn_random_herbivores(&mut self, count: usize, terrain: &Terrain, rng: &mut WorldRng) {
+        //Attempts to place count herbivores at random spots, skipping any that land underwater
+        for _i in 0..count {
+            let (x, z) = (rng.random_range(0.0 .. TERRAIN_GRID_ROWS as f32), rng.random_range(0.0 .. TERRAIN_GRID_ROWS as f32));
+
+            if terrain.get_height(x, z) < terrain.water_height || self.herbivores.len() >= MAX_HERBIVORES {
+                continue;
+            }
+
+            self.herbivores.push(Herbivore::new(x, z, 100.0, rng));
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod herbivore;
+pub mod herbivore_vec;
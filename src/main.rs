@@ -1,9 +1,17 @@
 #![allow(dead_code)]
+#![allow(
+    clippy::needless_return,
+    clippy::single_match,
+    clippy::collapsible_match,
+    clippy::module_inception,
+    clippy::too_many_arguments,
+    clippy::clone_on_copy,
+    clippy::needless_range_loop
+)]
 
 #[macro_use]
 extern crate glium;
 use glium::Surface;
-use glium::winit::dpi::LogicalSize;
 
 use std::time::Instant;
 use std::fs;
@@ -15,15 +23,28 @@ mod plant;
 mod render;
 mod terrain;
 mod world;
+mod headless;
+mod herbivore;
 
 use crate::plant::Plant;
-use crate::render::vector_math;
 use crate::plant::genome::PlantGenome;
+use crate::plant::plant_option_vec::PlantHandle;
 use crate::world::World;
 use crate::render::camera::CameraState;
 use crate::terrain::TERRAIN_CELL_WIDTH;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--print-genome") {
+        //Prints the spawn genome in the text format, as a starting point for writing new genome files
+        print!("{}", headless::spawn_genome_from_args(&args).to_text());
+        return;
+    }
+    if args.iter().any(|arg| arg == "--headless") {
+        headless::run_headless(headless::HeadlessOptions::from_args(&args));
+        return;
+    }
+
     let event_loop = glium::winit::event_loop::EventLoop::builder()
         .build()
         .expect("event loop building");
@@ -44,14 +65,22 @@ fn main() {
     let program = glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None).unwrap();
 
     let start_time = Instant::now();
-    let mut total_time: f64 = 0.0;
-    let mut delta_time: f64 = 0.0;
     let mut prev_instant = Instant::now();
-    let mut total_ticks: u64 = 0;
-
-    let mut world: World = World::world_init();
+    let mut session_ticks: u64 = 0;
+
+    let snapshot_path: String = headless::snapshot_path_from_args(&args);
+    let spawn_genome: PlantGenome = headless::spawn_genome_from_args(&args);
+    let mut world: World = match args.iter().any(|arg| arg == "--load") {
+        true => World::load_snapshot(&snapshot_path).expect("Unable to load world snapshot"),
+        false => {
+            let mut world = World::world_init(headless::world_seed_from_args(&args));
+            world.clock = headless::world_clock_from_args(&args);
+            world
+        }
+    };
 
     let mut camera: CameraState = CameraState::new();
+    let mut selected_plant: Option<PlantHandle> = None;
 
     let params = glium::DrawParameters {
         depth: glium::Depth {
@@ -73,20 +102,22 @@ fn main() {
                 glium::winit::event::WindowEvent::CloseRequested => window_target.exit(),
                 glium::winit::event::WindowEvent::RedrawRequested => {
                     let instant_now = Instant::now();
-                    total_time = instant_now.duration_since(start_time).as_secs_f64();
-                    delta_time = instant_now.duration_since(prev_instant).as_secs_f64();
+                    let total_time = instant_now.duration_since(start_time).as_secs_f64();
+                    let delta_time = instant_now.duration_since(prev_instant).as_secs_f64();
                     prev_instant = instant_now;
 
                     camera.update(delta_time as f32, &world);
 
                     let mut target = display.draw();
-                    target.clear_color_and_depth((0.60, 0.75, 0.95, 1.0), 1.0);
+                    let sky_color = world.clock.sky_color(world.total_ticks);
+                    target.clear_color_and_depth((sky_color.0, sky_color.1, sky_color.2, 1.0), 1.0);
 
                     // 20 ticks per second
-                    while total_ticks < (total_time * 20.0) as u64 {
-                        total_ticks += 1;
-                        world.tick(total_ticks, &display, &camera);
+                    while session_ticks < (total_time * 20.0) as u64 {
+                        session_ticks += 1;
+                        world.tick(Some(camera.position));
                     }
+                    world.upload_models(&display);
 
                     world.render(total_time as f32, &mut target, &program, &display, &camera, &params);
 
@@ -103,58 +134,87 @@ fn main() {
                         glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::KeyP) => {
                             if event.state.is_pressed() {
                                 println!("\n\nNumber of concurrent Plants: {}", world.plants.internal_vec.len());
-
-                                let mut closest_plant_index: Option<&Plant> = None;
-                                let mut closest_plant_dist: Option<f32> = None;
-                                for i in &world.plants.internal_vec {
-                                    match i {
-                                        Some(plant) => {
-                                            let dist = vector_math::dist_xz(plant.root_position, vector_math::scalar_multiple(1.0 / TERRAIN_CELL_WIDTH, camera.position));
-                                            if closest_plant_index.is_none() || dist < closest_plant_dist.unwrap() {
-                                                closest_plant_index = Some(&plant);
-                                                closest_plant_dist = Some(dist);
-                                            }
-                                        }
-                                        None => ()
+                                println!("Number of dispersed seeds: {}", world.plants.seeds.len());
+                                println!("Number of herbivores: {}", world.herbivores.herbivores.len());
+                                println!("Day {}, season {:.2}, {} weather events", world.clock.day(world.total_ticks), world.clock.season(world.total_ticks), world.weather.events.len());
+                                let death_counts: Vec<String> = world.plants.death_counts().iter().map(|(cause, count)| format!("{} {}", count, cause.name())).collect();
+                                println!("Last {} deaths: {}", world.plants.death_records.len(), death_counts.join(", "));
+
+                                let camera_x = camera.position.0 / TERRAIN_CELL_WIDTH;
+                                let camera_z = camera.position.2 / TERRAIN_CELL_WIDTH;
+                                match world.plants.spatial_index.nearest(camera_x, camera_z, f32::MAX) {
+                                    Some((plant_index, dist)) => {
+                                        let plant: &Plant = world.plants.internal_vec[plant_index].as_ref().unwrap();
+                                        println!("Nearest plant ({} units): ({:?})", dist, *plant);
+                                        println!("Nearest plant genome:\n{}", plant.genome.to_text());
+                                        println!("Selected plant {}, press L to check on it", plant.id);
+                                        selected_plant = world.plants.handle(plant_index);
                                     }
+                                    None => ()
                                 }
-
-                                match closest_plant_index {
+                            }
+                        },
+                        glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::KeyL) => {
+                            if event.state.is_pressed() {
+                                //Follows up on the plant last selected with P, along with whichever of its parents are still around
+                                match selected_plant.and_then(|handle| world.plants.get(handle)) {
                                     Some(plant) => {
-                                        println!("Nearest plant ({} units): ({:?})", closest_plant_dist.unwrap(), *plant);
+                                        println!("\n\nSelected plant: ({:?}), age {} ticks", *plant, plant.age_ticks);
+                                        for (parent_name, parent_id) in [("Mother", plant.mother_id), ("Father", plant.father_id)] {
+                                            match parent_id {
+                                                Some(parent_id) => match world.plants.find_by_id(parent_id) {
+                                                    Some(_) => println!("{} {} is still alive", parent_name, parent_id),
+                                                    None => println!("{} {} has died", parent_name, parent_id)
+                                                },
+                                                None => ()
+                                            }
+                                        }
+                                    },
+                                    None => {
+                                        println!("\n\nThe selected plant has died, press P to select another");
                                     }
-                                    None => ()
                                 }
                             }
                         },
                         glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::KeyK) => {
                             if event.state.is_pressed() {
                                 let new_plant = plant::Plant::new(
-                                    PlantGenome::human_made_tree_genome(), 
+                                    spawn_genome.clone(), 
                                     camera.position.0 / TERRAIN_CELL_WIDTH, 
                                     camera.position.2 / TERRAIN_CELL_WIDTH, 
                                     100.0, 
-                                    &world.terrain
+                                    &world.terrain,
+                                    &mut world.rng
                                 );
                                 world.plants.add_plant(new_plant);
                             }
                         },glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::KeyR) => {
                             if event.state.is_pressed() {
-                                for _i in 0..100 {
-                                    let (x, z) = (rand::random_range(0.0 .. terrain::TERRAIN_GRID_ROWS as f32), rand::random_range(0.0 .. terrain::TERRAIN_GRID_ROWS as f32));
-
-                                    if world.terrain.get_height(x, z) < world.terrain.water_height {
-                                        continue;
-                                    }
-
-                                    let new_plant = plant::Plant::new(
-                                        PlantGenome::human_made_tree_genome(), 
-                                        x,
-                                        z,
-                                        100.0, 
-                                        &world.terrain
-                                    );
-                                    world.plants.add_plant(new_plant);
+                                world.spawn_random_plants(100, &spawn_genome);
+                            }
+                        },
+                        glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::KeyH) => {
+                            if event.state.is_pressed() {
+                                world.spawn_random_herbivores(10);
+                            }
+                        },
+                        glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::F5) => {
+                            if event.state.is_pressed() {
+                                match world.save_snapshot(&snapshot_path) {
+                                    Ok(()) => println!("Saved world at tick {} to {}", world.total_ticks, snapshot_path),
+                                    Err(error) => println!("Unable to save world to {}: {}", snapshot_path, error)
+                                }
+                            }
+                        },
+                        glium::winit::keyboard::PhysicalKey::Code(glium::winit::keyboard::KeyCode::F9) => {
+                            if event.state.is_pressed() {
+                                match World::load_snapshot(&snapshot_path) {
+                                    Ok(loaded_world) => {
+                                        world = loaded_world;
+                                        selected_plant = None;
+                                        println!("Loaded world at tick {} from {}", world.total_ticks, snapshot_path);
+                                    },
+                                    Err(error) => println!("Unable to load world from {}: {}", snapshot_path, error)
                                 }
                             }
                         },
@@ -1,26 +1,68 @@
 use std::f32::consts::PI;
 
 use crate::plant::growth_priority_item::NewOffshootPriorityItem;
-use crate::rand::Rng;
+use crate::plant::hormone::HORMONE_COUNT;
+use crate::plant::genome::{PlantGenome, Phyllotaxis};
+use crate::plant::leaf::{Leaf, BRANCH_MAX_LEAVES};
+use rand::Rng;
+
+use crate::world::WorldRng;
+use crate::render::mat4_def::Mat4;
+use crate::world::world_clock::SunState;
+
+pub const BRANCH_MAX_OFFSHOOTS: usize = 6;
+const GOLDEN_ANGLE: f32 = 2.399_963;      // Radians, the turn between successive leaves in most spirals found in nature
 
 pub struct BranchConnection{
     pub branch_index: usize, 
     pub yaw: f32, 
     pub pitch: f32,
-    pub along_length: f32
+    pub along_length: f32,
+    pub droop: f32              // Extra pitch from the branch sagging under its load
 }
 
 impl BranchConnection {
-    pub fn new(new_offshoot_priority_item: &NewOffshootPriorityItem, new_index: usize) -> BranchConnection {
-        let along_length: f32 = 1.0 - (1.0 - new_offshoot_priority_item.placement_straightness) * rand::rng().random_range(0.0 .. 1.0);
+    pub fn new(new_offshoot_priority_item: &NewOffshootPriorityItem, new_index: usize, siblings: &[BranchConnection], genome: &PlantGenome, rng: &mut WorldRng) -> BranchConnection {
+        //siblings are the offshoots the parent branch already has, the genome's phyllotaxis decides where this one goes relative to them
+        let mut along_length: f32 = 1.0 - (1.0 - new_offshoot_priority_item.placement_straightness) * rng.random_range(0.0 .. 1.0);
+        let sibling_count = siblings.len();
+
+        let yaw: f32 = match genome.phyllotaxis {
+            Phyllotaxis::Random => rng.random_range(0.0 .. 2.0*PI),
+            Phyllotaxis::Spiral => (sibling_count as f32 * GOLDEN_ANGLE).rem_euclid(2.0*PI),
+            Phyllotaxis::Opposite => {
+                //The second of each pair grows from the same point as the first
+                if sibling_count % 2 == 1 {
+                    along_length = siblings[sibling_count - 1].along_length;
+                }
+                (sibling_count % 2) as f32 * PI + (sibling_count / 2) as f32 * PI * 0.5
+            },
+            Phyllotaxis::Whorled => {
+                match siblings.first() {
+                    Some(first_sibling) => {
+                        along_length = first_sibling.along_length;
+                    },
+                    None => ()
+                }
+                sibling_count as f32 * 2.0 * PI / genome.max_offshoot_count() as f32
+            }
+        };
 
         return BranchConnection { 
             branch_index: new_index, 
-            yaw: rand::rng().random_range(0.0 .. 2.0*PI), 
-            pitch: (1.0 - along_length) * PI * 0.5,
-            along_length
+            yaw, 
+            pitch: genome.offshoot_pitch + (1.0 - along_length) * PI * 0.5,
+            along_length,
+            droop: 0.0
         }
     }
+
+    pub fn offshoot_matrix(&self, parent_length_real: f32, parent_matrix: &Mat4) -> Mat4 {
+        return Mat4::rotation_y(self.yaw) * 
+               Mat4::rotation_x(self.pitch + self.droop) * 
+               Mat4::translation(0.0, self.along_length * parent_length_real, 0.0) * 
+               parent_matrix.clone();
+    }
 }
 
 pub struct Branch {
@@ -30,19 +72,24 @@ pub struct Branch {
 
     pub length: f32,
 
-    pub offshoot_1: Option<BranchConnection>,
-    pub offshoot_2: Option<BranchConnection>
+    pub hormones: [f32; HORMONE_COUNT],     // Indexed by Hormone::index
+    pub collected_sunlight: f32,            // Sunlight the branch caught the last time the plant ticked
+
+    pub offshoots: Vec<BranchConnection>,   // At most the genome's max_offshoot_count
+    pub leaves: Vec<Leaf>                   // At most max_leaf_count
 }
 
 impl Branch {
-    pub fn new(strength: f32, photoreceptiveness: f32, water_intake: f32, length: f32) -> Branch {
+    pub fn new(strength: f32, photoreceptiveness: f32, water_intake: f32, length: f32, rng: &mut WorldRng) -> Branch {
         return Branch {
-            strength: strength + rand::random_range(-0.03 .. 0.03),
-            photoreceptiveness: photoreceptiveness + rand::random_range(-0.03 .. 0.03),
-            water_intake: water_intake + rand::random_range(-0.03 .. 0.03),
-            length: length * rand::random_range(0.9 .. 1.1),
-            offshoot_1: None,
-            offshoot_2: None,
+            strength: strength + rng.random_range(-0.03 .. 0.03),
+            photoreceptiveness: photoreceptiveness + rng.random_range(-0.03 .. 0.03),
+            water_intake: water_intake + rng.random_range(-0.03 .. 0.03),
+            length: length * rng.random_range(0.9 .. 1.1),
+            hormones: [0.0; HORMONE_COUNT],
+            collected_sunlight: 0.0,
+            offshoots: vec![],
+            leaves: vec![],
         }
     }
 
@@ -55,38 +102,67 @@ impl Branch {
     }
 
     pub fn calculate_homeostasis(&self) -> f32 {
-        return 0.02 + 0.05 * self.length * (self.strength + self.photoreceptiveness + self.water_intake).powi(2);
+        let leaf_homeostasis: f32 = self.leaves.iter().map(|leaf| leaf.calculate_homeostasis()).sum();
+        return 0.02 + 0.05 * self.length * (self.strength + self.photoreceptiveness + self.water_intake).powi(2) + leaf_homeostasis;
     }
 
-    pub fn calculate_collect_sunlight(&self, depth: usize) -> f32 {
-        return 1.0 * self.length * self.photoreceptiveness * (1.0 + depth as f32 / 5.0); //TODO: Make this depend on height
+    pub fn calculate_leaf_cost(&self) -> f32 {
+        return self.leaves.iter().map(|leaf| leaf.calculate_cost()).sum();
     }
 
-    pub fn calculate_collect_water(&self, depth: usize) -> f32 {
-        return 2.5 * self.length * self.water_intake / (1.0 + depth as f32 / 5.0) as f32;
+    pub fn max_leaf_count(&self) -> usize {
+        //Photoreceptive branches are the ones that can carry leaves
+        return ((self.photoreceptiveness.max(0.0) * BRANCH_MAX_LEAVES as f32).ceil() as usize).min(BRANCH_MAX_LEAVES);
     }
 
-    pub fn add_offshoot(&mut self, branch_connection: BranchConnection) {
-        match self.offshoot_1 {
-            None => {
-                self.offshoot_1 = Some(branch_connection);
-                return;
-            }
-            Some(_) => ()
-        }
-        match self.offshoot_2 {
-            None => {
-                self.offshoot_2 = Some(branch_connection);
-                return;
-            }
-            Some(_) => ()
+    pub fn real_length(&self) -> f32 {
+        return 1.0 + self.length * 10.0;
+    }
+
+    pub fn calculate_collect_sunlight(&self, matrix: &Mat4, root_height: f32, light: f32, sun: &SunState) -> f32 {
+        //matrix places the branch relative to the root. All of the branch's sunlight is caught by its leaves,
+        //light is how much of the sun gets past the shade above the branch
+        let branch_length_real = self.real_length();
+        let mut sunlight: f32 = 0.0;
+        for leaf in &self.leaves {
+            sunlight += leaf.calculate_collect_sunlight(&leaf.leaf_matrix(branch_length_real, matrix), root_height, light, sun);
         }
-        panic!("Attempted to add an offshoot to a branch with two existing children");
+        return sunlight;
+    }
+
+    pub fn calculate_shade(&self) -> f32 {
+        //How much this branch darkens everything below it, mostly down to its leaves
+        let leaf_shade: f32 = self.leaves.iter().map(|leaf| leaf.calculate_shade()).sum();
+        return 0.0016 * self.real_length() + leaf_shade;
+    }
+
+    pub fn calculate_mass(&self) -> f32 {
+        return 0.1 * self.real_length() * (0.5 + self.strength.max(0.0) + self.photoreceptiveness.max(0.0) + self.water_intake.max(0.0));
+    }
+
+    pub fn calculate_load_capacity(&self) -> f32 {
+        //How much weight times lever arm the branch can hold up before it starts to sag
+        return 2.0 + 400.0 * self.strength.max(0.0).powi(2);
+    }
+
+    pub fn calculate_wind_overload(&self, wind: f32) -> f32 {
+        //How far past breaking point a wind of this strength pushes the branch. Long leafy branches catch the most wind and only strength holds them up
+        return 2.0 * wind * self.length * (0.5 + self.photoreceptiveness.max(0.0)) - self.strength;
+    }
+
+    pub fn calculate_collect_water(&self, depth: usize, water_availability: f32) -> f32 {
+        //Water has to be carried up from the roots, so deeper branches get less of it
+        return 2.5 * self.length * self.water_intake * water_availability / (1.0 + depth as f32 / 5.0);
+    }
+
+    pub fn add_offshoot(&mut self, branch_connection: BranchConnection) {
+        debug_assert!(self.offshoots.len() < BRANCH_MAX_OFFSHOOTS, "Attempted to add an offshoot to a branch with {} existing children", BRANCH_MAX_OFFSHOOTS);
+        self.offshoots.push(branch_connection);
     }
 }
 
-impl From<&NewOffshootPriorityItem> for Branch {
-    fn from(new_offshoot_priority_item: &NewOffshootPriorityItem) -> Branch { 
-        return Branch::new(new_offshoot_priority_item.strength, new_offshoot_priority_item.photoreceptiveness, new_offshoot_priority_item.water_intake, new_offshoot_priority_item.length);
+impl Branch {
+    pub fn from_new_offshoot_item(new_offshoot_priority_item: &NewOffshootPriorityItem, rng: &mut WorldRng) -> Branch { 
+        return Branch::new(new_offshoot_priority_item.strength, new_offshoot_priority_item.photoreceptiveness, new_offshoot_priority_item.water_intake, new_offshoot_priority_item.length, rng);
     }
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
-use rand::random_range;
+use rand::Rng;
 
-use crate::plant::{Plant, plant::PLANT_MAX_BRANCH_COUNT};
+use crate::plant::{Plant, plant::PLANT_MAX_BRANCH_COUNT, plant::PLANT_MAX_ROOT_COUNT};
 use crate::plant::genome::{OffshootSelection, RuleOutcome};
-use crate::terrain::Terrain;
+use crate::plant::hormone::{Hormone, HORMONE_TRANSPORT_RATE};
+use crate::terrain::{Terrain, TERRAIN_CELL_WIDTH};
 
 use std::collections::BinaryHeap;
 use crate::plant::growth_priority_item::GrowthPriorityItem;
@@ -10,6 +11,9 @@ use crate::render::branch_model::PlantModelMode;
 
 use crate::render::Vertex;
 use crate::render::mat4_def::Mat4;
+use crate::world::{WorldRng, LightGrid};
+use crate::world::light_grid::ShadeContribution;
+use crate::world::world_clock::SunState;
 
 impl Plant {
     pub fn execute_branch_and_update_model_recursive(
@@ -18,42 +22,52 @@ impl Plant {
         growth_priority_heap: &mut BinaryHeap<GrowthPriorityItem>, 
         depth: usize, 
         terrain: &Terrain,
+        sun: &SunState,
+        water_availability: f32,
+        light_grid: &LightGrid,
+        shade_contributions: &mut Vec<ShadeContribution>,
 
         plant_vertices: &mut Vec<Vertex>,
         plant_indices: &mut Vec<u32>,
         matrix: Mat4,
 
-        model_mode: PlantModelMode
+        model_mode: PlantModelMode,
+        rng: &mut WorldRng
     ) {
         *homeostasis += self.branches[branch_index].calculate_homeostasis();
-
-        self.current_sunlight += self.branches[branch_index].calculate_collect_sunlight(depth);
-        self.current_water += self.branches[branch_index].calculate_collect_water(depth);
-
-        self.execute_branch_genome(branch_index, growth_priority_heap, depth, terrain);
-
-        let branch_length_real = 1.0 + self.branches[branch_index].length * 10.0;
-        match &self.branches[branch_index].offshoot_1 {
-            Some(branch_connection) => {
-                let offshoot_1_matrix = Mat4::rotation_y(branch_connection.yaw) * 
-                                              Mat4::rotation_x(branch_connection.pitch) * 
-                                              Mat4::translation(0.0, branch_connection.along_length * branch_length_real, 0.0) * 
-                                              matrix.clone();
-
-                self.execute_branch_and_update_model_recursive(homeostasis, branch_connection.branch_index, growth_priority_heap, depth + 1, terrain, plant_vertices, plant_indices, offshoot_1_matrix, model_mode);
-            },
-            None => ()
-        }
-        match &self.branches[branch_index].offshoot_2 {
-            Some(branch_connection) => {
-                let offshoot_2_matrix = Mat4::rotation_y(branch_connection.yaw) * 
-                                              Mat4::rotation_x(branch_connection.pitch) * 
-                                              Mat4::translation(0.0, branch_connection.along_length * branch_length_real, 0.0) * 
-                                              matrix.clone();
-
-                self.execute_branch_and_update_model_recursive(homeostasis, branch_connection.branch_index, growth_priority_heap, depth + 1, terrain, plant_vertices, plant_indices, offshoot_2_matrix, model_mode);
-            },
-            None => ()
+        self.branches[branch_index].decay_hormones();
+
+        //The middle of the branch decides which light grid voxel it reads its light from and casts its shade into
+        let midpoint = matrix.mul_vec3_as_slice([0.0, self.branches[branch_index].real_length() * 0.5, 0.0]);
+        let (midpoint_x, midpoint_height, midpoint_z) = (
+            self.root_position.0 + midpoint[0] / TERRAIN_CELL_WIDTH, 
+            self.root_position.1 + midpoint[1], 
+            self.root_position.2 + midpoint[2] / TERRAIN_CELL_WIDTH
+        );
+        let light = light_grid.light_at(midpoint_x, midpoint_z, midpoint_height);
+        shade_contributions.push(LightGrid::shade_contribution(midpoint_x, midpoint_z, midpoint_height, self.branches[branch_index].calculate_shade()));
+
+        let collected_sunlight = self.branches[branch_index].calculate_collect_sunlight(&matrix, self.root_position.1, light, sun);
+        self.branches[branch_index].collected_sunlight = collected_sunlight;
+        self.current_sunlight += collected_sunlight;
+        self.current_water += self.branches[branch_index].calculate_collect_water(depth, water_availability);
+
+        self.execute_branch_genome(branch_index, growth_priority_heap, depth, terrain, sun, rng);
+
+        //Cytokinin is handed up to the offshoots before they run their rules, auxin comes back down once they have.
+        //Either way it cascades through the whole tree in one pass, fading a little at every branch it passes through
+        let offshoot_count = self.branches[branch_index].offshoots.len();
+        let cytokinin_share = self.branches[branch_index].hormone(Hormone::Cytokinin) * HORMONE_TRANSPORT_RATE / offshoot_count.max(1) as f32;
+
+        let branch_length_real = self.branches[branch_index].real_length();
+        for offshoot_number in 0..offshoot_count {
+            let branch_connection = &self.branches[branch_index].offshoots[offshoot_number];
+            let offshoot_index = branch_connection.branch_index;
+            let offshoot_matrix = branch_connection.offshoot_matrix(branch_length_real, &matrix);
+
+            self.move_hormone(Hormone::Cytokinin, branch_index, offshoot_index, cytokinin_share);
+            self.execute_branch_and_update_model_recursive(homeostasis, offshoot_index, growth_priority_heap, depth + 1, terrain, sun, water_availability, light_grid, shade_contributions, plant_vertices, plant_indices, offshoot_matrix, model_mode, rng);
+            self.move_hormone(Hormone::Auxin, offshoot_index, branch_index, self.branches[offshoot_index].hormone(Hormone::Auxin) * HORMONE_TRANSPORT_RATE);
         }
         
         match model_mode {
@@ -66,13 +80,13 @@ impl Plant {
         }
     }   
 
-    fn execute_branch_genome(&mut self, branch_index: usize, growth_priority_heap: &mut BinaryHeap<GrowthPriorityItem>, depth: usize, terrain: &Terrain) {
+    fn execute_branch_genome(&mut self, branch_index: usize, growth_priority_heap: &mut BinaryHeap<GrowthPriorityItem>, depth: usize, terrain: &Terrain, sun: &SunState, rng: &mut WorldRng) {
         for genome_rule in &self.genome.rules {
 
 
 
             // Giant match statement as Rust god (Ferris) intended
-            match genome_rule.evaluate(depth, &self.branches[branch_index], self, terrain) {
+            match genome_rule.evaluate(depth, &self.branches[branch_index], self, terrain, sun) {
                 Some(rule_outcome) => match rule_outcome {
                     
                     /////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -86,36 +100,14 @@ impl Plant {
 
 
                     RuleOutcome::KillOffshoot(offshoot_selection) => {
-                        let branch_target_index: usize;
-                        let returned_energy: f32;
-
-                        match offshoot_selection {
-                            OffshootSelection::One => {
-                                match &self.branches[branch_index].offshoot_1 {
-                                    Some(connection) => {
-                                        returned_energy = self.branches[connection.branch_index].calculate_cost();
-                                        branch_target_index = connection.branch_index;
-                                    }
-                                    None => {
-                                        continue;
-                                    }
-                                }
-                            },
-                            OffshootSelection::Two => {
-                                match &self.branches[branch_index].offshoot_2 {
-                                    Some(connection) => {
-                                        returned_energy = self.branches[connection.branch_index].calculate_cost();
-                                        branch_target_index = connection.branch_index;
-                                    }
-                                    None => {
-                                        continue;
-                                    }
-                                }
+                        let branch_target_index: usize = match self.select_offshoot(branch_index, offshoot_selection, rng) {
+                            Some(offshoot_index) => offshoot_index,
+                            None => {
+                                continue;
                             }
-                        }
+                        };
 
-                        self.delete_branch_recursive(branch_target_index);
-                        self.current_energy += returned_energy;
+                        self.branch_kill_requests.push(branch_target_index);
                         break;
                     },
 
@@ -134,7 +126,7 @@ impl Plant {
                             *photoreceptiveness_factor, 
                             *water_intake_factor, 
                             *length_factor, 
-                            *priority + random_range(-1.0 .. 1.0)
+                            *priority + rng.random_range(-1.0 .. 1.0)
                         ));
                         break;
                     },
@@ -149,7 +141,7 @@ impl Plant {
                         water_intake,
                         length,
                     } => {
-                        if self.branches.len() > PLANT_MAX_BRANCH_COUNT || (self.branches[branch_index].offshoot_1.is_some() && self.branches[branch_index].offshoot_2.is_some()) {
+                        if self.branches.len() > PLANT_MAX_BRANCH_COUNT || self.branches[branch_index].offshoots.len() >= self.genome.max_offshoot_count() {
                             continue;
                         }
                         growth_priority_heap.push(GrowthPriorityItem::new_offshoot_request(
@@ -159,9 +151,78 @@ impl Plant {
                             *photoreceptiveness, 
                             *water_intake, 
                             *length, 
-                            *priority + random_range(-1.0 .. 1.0)
+                            *priority + rng.random_range(-1.0 .. 1.0)
                         ));
                         break;
+                    },
+
+
+
+                    RuleOutcome::RequestNewRoot { 
+                        priority,
+                        spread,
+                        water_intake,
+                        length
+                    } => {
+                        if self.roots.len() >= PLANT_MAX_ROOT_COUNT {
+                            continue;
+                        }
+                        growth_priority_heap.push(GrowthPriorityItem::new_root_request(
+                            branch_index, 
+                            *spread, 
+                            *water_intake, 
+                            *length, 
+                            *priority + rng.random_range(-1.0 .. 1.0)
+                        ));
+                        break;
+                    },
+
+
+
+                    RuleOutcome::KillRoot => {
+                        if self.roots.is_empty() {
+                            continue;
+                        }
+                        self.root_kill_requested = true;
+                        break;
+                    },
+
+
+
+                    RuleOutcome::ProduceHormone { hormone, amount } => {
+                        self.branches[branch_index].produce_hormone(*hormone, *amount);
+                        continue;
+                    },
+
+
+
+                    RuleOutcome::RequestNewLeaf { 
+                        priority,
+                        area,
+                        angle,
+                        lifespan
+                    } => {
+                        if self.branches[branch_index].leaves.len() >= self.branches[branch_index].max_leaf_count() {
+                            continue;
+                        }
+                        growth_priority_heap.push(GrowthPriorityItem::new_leaf_request(
+                            branch_index, 
+                            *area, 
+                            *angle, 
+                            *lifespan, 
+                            *priority + rng.random_range(-1.0 .. 1.0)
+                        ));
+                        break;
+                    },
+
+
+
+                    RuleOutcome::ShedLeaves => {
+                        if self.branches[branch_index].leaves.is_empty() {
+                            continue;
+                        }
+                        self.leaf_shed_requests.push(branch_index);
+                        break;
                     }
 
                     /////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -176,4 +237,45 @@ impl Plant {
 
         }
     }
-}
\ No newline at end of file
+
+    fn select_offshoot(&self, branch_index: usize, offshoot_selection: &OffshootSelection, rng: &mut WorldRng) -> Option<usize> {
+        //Which of the branch's offshoots a KillOffshoot outcome prunes, if it has any. Ties go to the first offshoot
+        let offshoots: Vec<usize> = self.branches[branch_index].offshoots.iter().map(|connection| connection.branch_index).collect();
+        if offshoots.is_empty() {
+            return None;
+        }
+
+        //The offshoot with the highest score gets pruned
+        let score = |offshoot_index: usize| -> f32 {
+            let offshoot = &self.branches[offshoot_index];
+            match offshoot_selection {
+                OffshootSelection::Longest => offshoot.length,
+                OffshootSelection::Weakest => -offshoot.strength,
+                OffshootSelection::LeastPhotoreceptive => -offshoot.photoreceptiveness,
+                OffshootSelection::MostCostlyPerYield => self.calculate_subtree_cost(offshoot_index) / (self.calculate_subtree_sunlight(offshoot_index) + 0.01),
+                _ => 0.0
+            }
+        };
+
+        match offshoot_selection {
+            OffshootSelection::One => {
+                return offshoots.first().copied();
+            },
+            OffshootSelection::Two => {
+                return offshoots.get(1).copied();
+            },
+            OffshootSelection::Random => {
+                return Some(offshoots[rng.random_range(0..offshoots.len())]);
+            },
+            _ => {
+                let mut selected = offshoots[0];
+                for offshoot_index in offshoots.iter().skip(1) {
+                    if score(*offshoot_index) > score(selected) {
+                        selected = *offshoot_index;
+                    }
+                }
+                return Some(selected);
+            }
+        }
+    }
+}
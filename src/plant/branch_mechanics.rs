@@ -0,0 +1,89 @@
+use crate::plant::Plant;
+use crate::render::mat4_def::Mat4;
+use crate::terrain::Terrain;
+use crate::terrain::nutrients::NITROGEN_PER_COST;
+
+// Every branch has to hold up everything growing off of it. The load on a branch is the weight of each branch above it times
+// how far out sideways from its base that weight hangs, so long horizontal limbs and tall top heavy plants strain the most.
+// Branches a bit past their strength droop, springing back up if they're unloaded again, and ones far past it snap off and fall to the ground.
+// A trunk that far past its strength takes the whole plant down with it.
+
+const BRANCH_DROOP_PER_LOAD: f32 = 0.2; // Radians a branch sags by per unit of load past its strength
+const BRANCH_MAX_DROOP: f32 = 0.6;
+const BRANCH_DROOP_RATE: f32 = 0.2;     // Fraction of the way a branch moves towards how far it should be sagging each plant tick
+const BRANCH_BREAK_LOAD: f32 = 4.0;     // Times a branch's strength it can carry before it snaps
+const TRUNK_COLLAPSE_LOAD: f32 = 8.0;   // Times the trunk's strength it can carry before the whole plant comes down, trunks being anchored by the roots
+
+impl Plant {
+    pub fn calculate_branch_loads(&self) -> Vec<f32> {
+        //Base and midpoint of every branch relative to the root, then for every branch the sum of weight times sideways distance over itself and everything above it
+        let mut positions: Vec<([f32; 3], [f32; 3])> = vec![([0.0; 3], [0.0; 3]); self.branches.len()];
+        self.calculate_branch_positions_recursive(0, Mat4::identity(), &mut positions);
+
+        let mut loads: Vec<f32> = vec![0.0; self.branches.len()];
+        for branch_index in 0..self.branches.len() {
+            loads[branch_index] = self.calculate_load_recursive(branch_index, positions[branch_index].0, &positions);
+        }
+        return loads;
+    }
+
+    fn calculate_branch_positions_recursive(&self, branch_index: usize, matrix: Mat4, positions: &mut Vec<([f32; 3], [f32; 3])>) {
+        let branch = &self.branches[branch_index];
+        positions[branch_index] = (matrix.mul_vec3_as_slice([0.0, 0.0, 0.0]), matrix.mul_vec3_as_slice([0.0, branch.real_length() * 0.5, 0.0]));
+
+        for connection in &branch.offshoots {
+            self.calculate_branch_positions_recursive(connection.branch_index, connection.offshoot_matrix(branch.real_length(), &matrix), positions);
+        }
+    }
+
+    fn calculate_load_recursive(&self, branch_index: usize, base: [f32; 3], positions: &[([f32; 3], [f32; 3])]) -> f32 {
+        let branch = &self.branches[branch_index];
+        let midpoint = positions[branch_index].1;
+        let lever_arm = ((midpoint[0] - base[0]).powi(2) + (midpoint[2] - base[2]).powi(2)).sqrt();
+
+        let mut load = branch.calculate_mass() * lever_arm;
+        for connection in &branch.offshoots {
+            load += self.calculate_load_recursive(connection.branch_index, base, positions);
+        }
+        return load;
+    }
+
+    pub fn apply_structural_load(&mut self, terrain: &mut Terrain) -> bool {
+        //Returns true when the trunk itself gave way, bringing the whole plant down with it
+        let loads = self.calculate_branch_loads();
+        if loads[0] / self.branches[0].calculate_load_capacity() > TRUNK_COLLAPSE_LOAD {
+            return true;
+        }
+
+        let mut broken: Vec<usize> = vec![];
+        for branch_index in 1..self.branches.len() {
+            let overload = loads[branch_index] / self.branches[branch_index].calculate_load_capacity();
+
+            if overload > BRANCH_BREAK_LOAD {
+                broken.push(branch_index);
+                continue;
+            }
+
+            self.droop_branch(branch_index, ((overload - 1.0) * BRANCH_DROOP_PER_LOAD).clamp(0.0, BRANCH_MAX_DROOP));
+        }
+
+        if broken.is_empty() {
+            return false;
+        }
+
+        let dropped_cost = self.delete_branches(&broken);
+        terrain.add_detritus(self.root_position.0, self.root_position.2, dropped_cost * NITROGEN_PER_COST);
+        return false;
+    }
+
+    fn droop_branch(&mut self, branch_index: usize, target_droop: f32) {
+        for parent in &mut self.branches {
+            for connection in &mut parent.offshoots {
+                if connection.branch_index == branch_index {
+                    connection.droop += (target_droop - connection.droop) * BRANCH_DROOP_RATE;
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::plant::Plant;
+
+pub const MAX_DEATH_RECORDS: usize = 1000;     // Only the most recent deaths are kept
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DeathCause {
+    Starvation,     // Ran out of energy
+    OldAge,         // Outlived the lifespan in its genome
+    Drowning,       // Its base stayed under the water for too long
+    Collapse,       // Its trunk gave way under the weight of the rest of the plant
+    Grazing         // Eaten
+}
+
+impl DeathCause {
+    pub const ALL: [DeathCause; 5] = [
+        DeathCause::Starvation,
+        DeathCause::OldAge,
+        DeathCause::Drowning,
+        DeathCause::Collapse,
+        DeathCause::Grazing
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "starvation",
+            DeathCause::OldAge => "old age",
+            DeathCause::Drowning => "drowning",
+            DeathCause::Collapse => "collapse",
+            DeathCause::Grazing => "grazing"
+        }
+    }
+}
+
+pub struct DeathRecord {
+    pub plant_id: u64,
+    pub mother_id: Option<u64>,
+    pub father_id: Option<u64>,
+    pub cause: DeathCause,
+    pub tick: u64,              // World tick the plant died on
+    pub age_ticks: u64,
+    pub x: f32,
+    pub z: f32,
+    pub branch_count: usize,
+    pub seeds_produced: u32
+}
+
+impl DeathRecord {
+    pub fn new(plant: &Plant, cause: DeathCause, tick: u64) -> DeathRecord {
+        return DeathRecord {
+            plant_id: plant.id,
+            mother_id: plant.mother_id,
+            father_id: plant.father_id,
+            cause,
+            tick,
+            age_ticks: plant.age_ticks,
+            x: plant.root_position.0,
+            z: plant.root_position.2,
+            branch_count: plant.branches.len(),
+            seeds_produced: plant.seeds_produced
+        }
+    }
+}
@@ -1,41 +1,71 @@
-use std::rc::Rc;
-
-use crate::plant::branch::Branch;
+use crate::plant::branch::{Branch, BRANCH_MAX_OFFSHOOTS};
+use crate::plant::hormone::{Hormone, HORMONE_MAX_LEVEL};
+use crate::plant::leaf::BRANCH_MAX_LEAVES;
 use crate::plant::Plant;
+use crate::plant::plant::{PLANT_MAX_BRANCH_COUNT, PLANT_MAX_ROOT_COUNT, PLANT_MAX_NITROGEN};
 use crate::terrain::Terrain;
+use crate::world::world_clock::SunState;
+
+use std::f32::consts::PI;
 
-use crate::rand::Rng;
+use rand::Rng;
 
-const MAX_GENOME_RULE_COUNT: usize = 8;
+pub const MAX_GENOME_RULE_COUNT: usize = 8;
+pub const MAX_RULE_CONDITION_SIZE: usize = 7;       // Most requirement tests and AND/OR/NOT nodes a single rule condition can be built from
 
 struct GenomeVal {
     val: f32,
     rand_factor: f32
 }
 
+#[derive(Clone, PartialEq)]
 pub enum OffshootSelection {
     One,
-    Two
+    Two,
+    Longest,
+    Weakest,
+    LeastPhotoreceptive,
+    MostCostlyPerYield,     // Costs the most for the sunlight it and everything growing off of it caught
+    Random
 }
 
-enum RuleReq {
+// How a branch spaces its offshoots out around itself
+#[derive(Clone, Copy, PartialEq)]
+pub enum Phyllotaxis {
+    Random,     // Any direction, anywhere along the branch
+    Spiral,     // Each offshoot turned the golden angle on from the last, like a pine cone
+    Opposite,   // In pairs facing away from each other, each pair turned a quarter turn from the last
+    Whorled     // All at the same point along the branch, evenly spaced around it
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RuleReq {
     BranchDepthReq,
     BranchStrengthReq,
     BranchPhotoreceptivenessReq,
     BranchWaterIntakeReq,
     BranchLengthReq,
+    BranchAuxinReq,
+    BranchCytokininReq,
+    BranchLeafReq,
 
     PlantEnergyReq,
     PlantWaterReq,
     PlantSunlightReq,
     PlantBranchReq,
+    PlantRootReq,
+    PlantNitrogenReq,
+    PlantAgeReq,
 
     TerrainHeightReq,
     TerrainDrynessReq,
     TerrainRockinessReq,
-    TerrainWaterlog
+    TerrainWaterlog,
+
+    SeasonReq
 }
 
+#[derive(Clone, PartialEq)]
 pub enum RuleOutcome {
     Exit,
     //JumpToRule(usize), //Removed for concerns of infinite loops
@@ -54,105 +84,340 @@ pub enum RuleOutcome {
         photoreceptiveness: f32,
         water_intake: f32,
         length: f32,
-    }
+    },
+    RequestNewRoot{
+        priority: f32,
+        spread: f32,
+        water_intake: f32,
+        length: f32
+    },
+    KillRoot,
+    ProduceHormone{                 // Unlike the other outcomes this doesn't stop the later rules from being checked
+        hormone: Hormone,
+        amount: f32
+    },
+    RequestNewLeaf{
+        priority: f32,
+        area: f32,
+        angle: f32,                 // Radians out from the branch
+        lifespan: f32               // In plant ticks
+    },
+    ShedLeaves
 }
 
-pub struct GenomeRule {
-    req: RuleReq,
-    min: f32,
-    max: f32,
-    outcome: RuleOutcome
-}
+impl RuleReq {
+    pub const ALL: [RuleReq; 20] = [
+        RuleReq::BranchDepthReq,
+        RuleReq::BranchStrengthReq,
+        RuleReq::BranchPhotoreceptivenessReq,
+        RuleReq::BranchWaterIntakeReq,
+        RuleReq::BranchLengthReq,
+        RuleReq::BranchAuxinReq,
+        RuleReq::BranchCytokininReq,
+        RuleReq::BranchLeafReq,
 
-impl GenomeRule {
-    pub fn evaluate(&self, depth: usize, branch: &Branch, plant: &Plant, terrain: &Terrain) -> Option<&RuleOutcome> {
-        let comp_val = match self.req {
+        RuleReq::PlantEnergyReq,
+        RuleReq::PlantWaterReq,
+        RuleReq::PlantSunlightReq,
+        RuleReq::PlantBranchReq,
+        RuleReq::PlantRootReq,
+        RuleReq::PlantNitrogenReq,
+        RuleReq::PlantAgeReq,
+
+        RuleReq::TerrainHeightReq,
+        RuleReq::TerrainDrynessReq,
+        RuleReq::TerrainRockinessReq,
+        RuleReq::TerrainWaterlog,
+
+        RuleReq::SeasonReq
+    ];
+
+    pub fn random<R: Rng>(rng: &mut R) -> RuleReq {
+        return Self::ALL[rng.random_range(0..Self::ALL.len())];
+    }
+
+    pub fn value(&self, depth: usize, branch: &Branch, plant: &Plant, terrain: &Terrain, sun: &SunState) -> f32 {
+        //What this requirement measures, for the branch at the given depth
+        match self {
             RuleReq::BranchDepthReq => depth as f32,
             RuleReq::BranchStrengthReq => branch.strength,
             RuleReq::BranchPhotoreceptivenessReq => branch.photoreceptiveness,
             RuleReq::BranchWaterIntakeReq => branch.water_intake,
             RuleReq::BranchLengthReq => branch.length,
+            RuleReq::BranchAuxinReq => branch.hormone(Hormone::Auxin),
+            RuleReq::BranchCytokininReq => branch.hormone(Hormone::Cytokinin),
+            RuleReq::BranchLeafReq => branch.leaves.len() as f32,
 
             RuleReq::PlantEnergyReq => plant.current_energy,
             RuleReq::PlantWaterReq => plant.current_water,
             RuleReq::PlantSunlightReq => plant.current_sunlight,
             RuleReq::PlantBranchReq => plant.branches.len() as f32,
+            RuleReq::PlantRootReq => plant.roots.len() as f32,
+            RuleReq::PlantNitrogenReq => plant.current_nitrogen,
+            RuleReq::PlantAgeReq => plant.age_ticks as f32,
+
+            RuleReq::TerrainHeightReq => terrain.get_height(plant.root_position.0, plant.root_position.2),
+            RuleReq::TerrainDrynessReq => terrain.get_dryness(plant.root_position.0, plant.root_position.2),
+            RuleReq::TerrainRockinessReq => terrain.get_rockiness(plant.root_position.0, plant.root_position.2),
+            RuleReq::TerrainWaterlog => terrain.get_waterlog(plant.root_position.0, plant.root_position.2),
+
+            RuleReq::SeasonReq => sun.season
+        }
+    }
+
+    pub fn typical_range(&self) -> (f32, f32) {
+        //Roughly the values this requirement takes on in practice, used to pick sensible windows for random and mutated rules
+        match self {
+            RuleReq::BranchDepthReq => (0.0, 12.0),
+            RuleReq::BranchStrengthReq => (0.0, 1.0),
+            RuleReq::BranchPhotoreceptivenessReq => (0.0, 1.0),
+            RuleReq::BranchWaterIntakeReq => (0.0, 1.0),
+            RuleReq::BranchLengthReq => (0.0, 1.5),
+            RuleReq::BranchAuxinReq => (0.0, HORMONE_MAX_LEVEL),
+            RuleReq::BranchCytokininReq => (0.0, HORMONE_MAX_LEVEL),
+            RuleReq::BranchLeafReq => (0.0, BRANCH_MAX_LEAVES as f32),
+
+            RuleReq::PlantEnergyReq => (0.0, 200.0),
+            RuleReq::PlantWaterReq => (0.0, 100.0),
+            RuleReq::PlantSunlightReq => (0.0, 100.0),
+            RuleReq::PlantBranchReq => (0.0, PLANT_MAX_BRANCH_COUNT as f32),
+            RuleReq::PlantRootReq => (0.0, PLANT_MAX_ROOT_COUNT as f32),
+            RuleReq::PlantNitrogenReq => (0.0, PLANT_MAX_NITROGEN),
+            RuleReq::PlantAgeReq => (0.0, 2000.0),
+
+            RuleReq::TerrainHeightReq => (-20.0, 80.0),
+            RuleReq::TerrainDrynessReq => (0.0, 1.0),
+            RuleReq::TerrainRockinessReq => (0.0, 1.0),
+            RuleReq::TerrainWaterlog => (-50.0, 10.0),
+
+            RuleReq::SeasonReq => (0.0, 4.0)
+        }
+    }
+}
+
+impl Phyllotaxis {
+    pub const ALL: [Phyllotaxis; 4] = [
+        Phyllotaxis::Random,
+        Phyllotaxis::Spiral,
+        Phyllotaxis::Opposite,
+        Phyllotaxis::Whorled
+    ];
+
+    pub fn random<R: Rng>(rng: &mut R) -> Phyllotaxis {
+        return Phyllotaxis::ALL[rng.random_range(0..Phyllotaxis::ALL.len())];
+    }
+}
+
+impl OffshootSelection {
+    pub fn random<R: Rng>(rng: &mut R) -> OffshootSelection {
+        match rng.random_range(0..7) {
+            0 => OffshootSelection::One,
+            1 => OffshootSelection::Two,
+            2 => OffshootSelection::Longest,
+            3 => OffshootSelection::Weakest,
+            4 => OffshootSelection::LeastPhotoreceptive,
+            5 => OffshootSelection::MostCostlyPerYield,
+            _ => OffshootSelection::Random
+        }
+    }
+}
+
+impl RuleOutcome {
+    pub fn random<R: Rng>(rng: &mut R) -> RuleOutcome {
+        match rng.random_range(0..9) {
+            0 => RuleOutcome::Exit,
+            1 => RuleOutcome::KillOffshoot(OffshootSelection::random(rng)),
+            2 => RuleOutcome::RequestModifyBranch { 
+                priority: rng.random_range(0.0 .. 10.0), 
+                strength_factor: rng.random_range(-1.0 .. 1.0), 
+                photoreceptiveness_factor: rng.random_range(-1.0 .. 1.0), 
+                water_intake_factor: rng.random_range(-1.0 .. 1.0), 
+                length_factor: rng.random_range(-1.0 .. 1.0) 
+            },
+            3 => RuleOutcome::RequestNewRoot { 
+                priority: rng.random_range(0.0 .. 10.0), 
+                spread: rng.random_range(0.0 .. 1.0), 
+                water_intake: rng.random_range(0.0 .. 1.0), 
+                length: rng.random_range(0.05 .. 1.0) 
+            },
+            4 => RuleOutcome::KillRoot,
+            5 => RuleOutcome::ProduceHormone { 
+                hormone: Hormone::random(rng), 
+                amount: rng.random_range(0.0 .. 1.0) 
+            },
+            6 => RuleOutcome::RequestNewLeaf { 
+                priority: rng.random_range(0.0 .. 10.0), 
+                area: rng.random_range(0.05 .. 1.0), 
+                angle: rng.random_range(0.0 .. PI), 
+                lifespan: rng.random_range(50.0 .. 2000.0) 
+            },
+            7 => RuleOutcome::ShedLeaves,
+            _ => RuleOutcome::RequestNewOffshoot { 
+                priority: rng.random_range(0.0 .. 10.0), 
+                placement_straightness: rng.random_range(0.0 .. 1.0), 
+                strength: rng.random_range(0.0 .. 1.0), 
+                photoreceptiveness: rng.random_range(0.0 .. 1.0), 
+                water_intake: rng.random_range(0.0 .. 1.0), 
+                length: rng.random_range(0.05 .. 1.0) 
+            }
+        }
+    }
+}
+
+// A rule's condition is a small tree of requirement tests combined with AND, OR and NOT.
+// And and Or always have at least two terms, and the whole tree is kept within MAX_RULE_CONDITION_SIZE nodes
+#[derive(Clone, PartialEq)]
+pub enum RuleCondition {
+    Req {
+        req: RuleReq,
+        min: f32,
+        max: f32
+    },
+    Not(Box<RuleCondition>),
+    And(Vec<RuleCondition>),
+    Or(Vec<RuleCondition>)
+}
+
+impl RuleCondition {
+    pub fn evaluate(&self, depth: usize, branch: &Branch, plant: &Plant, terrain: &Terrain, sun: &SunState) -> bool {
+        match self {
+            RuleCondition::Req { req, min, max } => {
+                let comp_val = req.value(depth, branch, plant, terrain, sun);
+                return *min <= comp_val && comp_val <= *max;
+            },
+            RuleCondition::Not(condition) => !condition.evaluate(depth, branch, plant, terrain, sun),
+            RuleCondition::And(conditions) => conditions.iter().all(|condition| condition.evaluate(depth, branch, plant, terrain, sun)),
+            RuleCondition::Or(conditions) => conditions.iter().any(|condition| condition.evaluate(depth, branch, plant, terrain, sun))
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        //Number of nodes in the tree
+        match self {
+            RuleCondition::Req { .. } => 1,
+            RuleCondition::Not(condition) => 1 + condition.size(),
+            RuleCondition::And(conditions) | RuleCondition::Or(conditions) => 1 + conditions.iter().map(|condition| condition.size()).sum::<usize>()
+        }
+    }
+
+    pub fn first_req(&self) -> RuleReq {
+        //The leftmost requirement tested, which crossover uses to line up similar rules
+        match self {
+            RuleCondition::Req { req, .. } => *req,
+            RuleCondition::Not(condition) => condition.first_req(),
+            RuleCondition::And(conditions) | RuleCondition::Or(conditions) => conditions[0].first_req()
+        }
+    }
 
-            RuleReq::TerrainHeightReq => plant.root_position.1,
-            RuleReq::TerrainDrynessReq => 0.0,
-            RuleReq::TerrainRockinessReq => 0.0,
-            RuleReq::TerrainWaterlog => 0.0
-        };
+    pub fn random_req<R: Rng>(rng: &mut R) -> RuleCondition {
+        let req = RuleReq::random(rng);
+        let (range_min, range_max) = req.typical_range();
 
-        if self.min <= comp_val && comp_val <= self.max {
+        let a: f32 = rng.random_range(range_min .. range_max);
+        let b: f32 = rng.random_range(range_min .. range_max);
+
+        return RuleCondition::Req { req, min: a.min(b), max: a.max(b) };
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct GenomeRule {
+    pub condition: RuleCondition,
+    pub outcome: RuleOutcome
+}
+
+impl GenomeRule {
+    pub fn evaluate(&self, depth: usize, branch: &Branch, plant: &Plant, terrain: &Terrain, sun: &SunState) -> Option<&RuleOutcome> {
+        if self.condition.evaluate(depth, branch, plant, terrain, sun) {
             return Some(&self.outcome);
         }
         return None;
     }
 
-    pub fn random() -> GenomeRule {
+    pub fn random<R: Rng>(rng: &mut R) -> GenomeRule {
+        //New rules start out testing a single requirement, mutation can build them up from there
         return GenomeRule { 
-            req: RuleReq::BranchDepthReq, 
-            min: rand::rng().random_range(0.0 .. 20.0), 
-            max: rand::rng().random_range(0.0 .. 20.0), 
-            outcome: RuleOutcome::Exit //TEMP 
+            condition: RuleCondition::random_req(rng),
+            outcome: RuleOutcome::random(rng)
         }
     }
 }
 
+#[derive(Clone, PartialEq)]
 pub struct PlantGenome {
     pub min_enegy_for_growth: f32,
     pub baby_energy: f32,
+    pub lifespan: f32,          // In plant ticks
+    pub pollination_range: f32, // In terrain cells
 
     pub sapling_strength: f32,
     pub sapling_photoreceptiveness: f32,
     pub sapling_water_intake: f32,
     pub sapling_length: f32,
 
+    pub max_offshoots: f32,     // Rounded to a whole number of offshoots each branch can grow
+    pub offshoot_pitch: f32,    // Radians offshoots lean out by on top of how far down their parent branch they grow
+    pub phyllotaxis: Phyllotaxis,
+
     pub rules: Vec<GenomeRule>
 }
 
 impl PlantGenome {
-    pub fn random() -> PlantGenome {
+    pub fn max_offshoot_count(&self) -> usize {
+        return (self.max_offshoots.round().max(0.0) as usize).clamp(1, BRANCH_MAX_OFFSHOOTS);
+    }
+
+    pub fn random<R: Rng>(rng: &mut R) -> PlantGenome {
         return PlantGenome { 
             min_enegy_for_growth: 10.0, 
             baby_energy: 100.0, 
+            lifespan: rng.random_range(300.0 .. 3000.0),
+            pollination_range: rng.random_range(0.0 .. 10.0),
 
             sapling_strength: 0.0, 
             sapling_photoreceptiveness: 0.0, 
             sapling_water_intake: 0.0, 
-            sapling_length: 10.0,
+            sapling_length: 1.0,
+
+            max_offshoots: rng.random_range(1.0 .. 5.0),
+            offshoot_pitch: rng.random_range(0.0 .. 1.5),
+            phyllotaxis: Phyllotaxis::random(rng),
 
-            rules: vec![GenomeRule::random(), GenomeRule::random(), GenomeRule::random(), GenomeRule::random()] 
+            rules: vec![GenomeRule::random(rng), GenomeRule::random(rng), GenomeRule::random(rng), GenomeRule::random(rng)] 
         }
     }
     pub fn human_made_tree_genome() -> PlantGenome {
         return PlantGenome { 
             min_enegy_for_growth: 34.0, 
-            baby_energy: 50.0, 
+            baby_energy: 75.0, 
+            lifespan: 3000.0,
+            pollination_range: 6.0,
 
             sapling_strength: 0.3, 
             sapling_photoreceptiveness: 0.9, 
             sapling_water_intake: 0.5, 
             sapling_length: 0.5,
 
+            max_offshoots: 2.0,
+            offshoot_pitch: 0.0,
+            phyllotaxis: Phyllotaxis::Random,
+
             rules: vec![
                 GenomeRule {
-                    req: RuleReq::BranchDepthReq,
-                    min: 6.5,
-                    max: 10.0,
+                    condition: RuleCondition::Req { req: RuleReq::PlantEnergyReq, min: -1.0, max: 15.0 },
                     outcome: RuleOutcome::Exit
                 },
                 GenomeRule {
-                    req: RuleReq::PlantEnergyReq,
-                    min: -1.0,
-                    max: 15.0,
-                    outcome: RuleOutcome::Exit
+                    condition: RuleCondition::Req { req: RuleReq::BranchLeafReq, min: -1.0, max: 2.5 },
+                    outcome: RuleOutcome::RequestNewLeaf { 
+                        priority: 8.0,
+                        area: 0.4,
+                        angle: 1.2,
+                        lifespan: 600.0
+                    }
                 },
                 GenomeRule {
-                    req: RuleReq::BranchDepthReq,
-                    min: -1.0,
-                    max: 4.5,
+                    condition: RuleCondition::Req { req: RuleReq::BranchDepthReq, min: -1.0, max: 4.5 },
                     outcome: RuleOutcome::RequestNewOffshoot { 
                         priority: 10.0,
                         placement_straightness: 0.5, 
@@ -163,9 +428,7 @@ impl PlantGenome {
                     }
                 },
                 GenomeRule {
-                    req: RuleReq::BranchDepthReq,
-                    min: 4.5,
-                    max: 10.0,
+                    condition: RuleCondition::Req { req: RuleReq::BranchDepthReq, min: 4.5, max: 6.5 },
                     outcome: RuleOutcome::RequestNewOffshoot { 
                         priority: 5.0, 
                         placement_straightness: 0.0,
@@ -176,15 +439,11 @@ impl PlantGenome {
                     }
                 },
                 GenomeRule {
-                    req: RuleReq::BranchDepthReq,
-                    min: 1.5,
-                    max: 99.0,
+                    condition: RuleCondition::Req { req: RuleReq::BranchDepthReq, min: 1.5, max: 99.0 },
                     outcome: RuleOutcome::Exit
                 },
                 GenomeRule {
-                    req: RuleReq::PlantBranchReq,
-                    min: 11.5,
-                    max: 99.0,
+                    condition: RuleCondition::Req { req: RuleReq::PlantBranchReq, min: 11.5, max: 99.0 },
                     outcome: RuleOutcome::RequestModifyBranch { 
                         strength_factor: 0.5, 
                         photoreceptiveness_factor: -1.0, 
@@ -194,9 +453,7 @@ impl PlantGenome {
                     }
                 },
                 GenomeRule {
-                    req: RuleReq::PlantWaterReq,
-                    min: 0.0,
-                    max: 30.0,
+                    condition: RuleCondition::Req { req: RuleReq::PlantWaterReq, min: 0.0, max: 30.0 },
                     outcome: RuleOutcome::RequestModifyBranch { 
                         strength_factor: 0.2, 
                         photoreceptiveness_factor: -0.3, 
@@ -204,6 +461,15 @@ impl PlantGenome {
                         length_factor: 0.8,
                         priority: 2.5
                     }
+                },
+                GenomeRule {
+                    condition: RuleCondition::Req { req: RuleReq::PlantRootReq, min: -1.0, max: 3.5 },
+                    outcome: RuleOutcome::RequestNewRoot { 
+                        priority: 4.0,
+                        spread: 0.6,
+                        water_intake: 0.6,
+                        length: 0.4
+                    }
                 }
             ] 
         }
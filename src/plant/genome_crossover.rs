@@ -0,0 +1,159 @@
+use rand::Rng;
+
+use crate::plant::genome::{PlantGenome, GenomeRule, MAX_GENOME_RULE_COUNT};
+
+#[derive(Clone, Copy)]
+pub enum CrossoverStrategy {
+    SinglePoint,    // Rules before a random cut come from the mother, the rest from the father
+    Uniform,        // Every scalar gene and every rule slot is picked from either parent independently
+    RuleAligned     // Rules whose conditions start with the same requirement are paired up and one of each pair is kept
+}
+
+#[inline]
+fn pick<R: Rng>(a: f32, b: f32, rng: &mut R) -> f32 {
+    return if rng.random_bool(0.5) { a } else { b };
+}
+
+impl PlantGenome {
+    pub fn crossover<R: Rng>(mother: &PlantGenome, father: &PlantGenome, strategy: CrossoverStrategy, rng: &mut R) -> PlantGenome {
+        let mut child: PlantGenome = match strategy {
+            CrossoverStrategy::SinglePoint => {
+                let cut = rng.random_range(0 ..= mother.rules.len().max(father.rules.len()));
+
+                let mut rules: Vec<GenomeRule> = vec![];
+                rules.extend(mother.rules.iter().take(cut).cloned());
+                rules.extend(father.rules.iter().skip(cut).cloned());
+
+                PlantGenome {
+                    rules,
+                    ..*mother
+                }
+            },
+            CrossoverStrategy::Uniform => {
+                let mut rules: Vec<GenomeRule> = vec![];
+                for i in 0..mother.rules.len().max(father.rules.len()) {
+                    let parent = if rng.random_bool(0.5) { mother } else { father };
+                    match parent.rules.get(i) {
+                        Some(rule) => rules.push(rule.clone()),
+                        None => ()
+                    }
+                }
+
+                PlantGenome::mix_scalars(mother, father, rules, rng)
+            },
+            CrossoverStrategy::RuleAligned => {
+                // Walks the mother's rules in order, pairing each with the first unused father rule whose condition starts with the same requirement
+                let mut father_used: Vec<bool> = vec![false; father.rules.len()];
+                let mut rules: Vec<GenomeRule> = vec![];
+
+                for mother_rule in &mother.rules {
+                    let partner = father.rules.iter().enumerate().position(|(i, father_rule)| !father_used[i] && father_rule.condition.first_req() == mother_rule.condition.first_req());
+
+                    match partner {
+                        Some(i) => {
+                            father_used[i] = true;
+                            rules.push(if rng.random_bool(0.5) { mother_rule.clone() } else { father.rules[i].clone() });
+                        },
+                        None => {
+                            if rng.random_bool(0.5) {
+                                rules.push(mother_rule.clone());
+                            }
+                        }
+                    }
+                }
+                for (i, father_rule) in father.rules.iter().enumerate() {
+                    if !father_used[i] && rng.random_bool(0.5) {
+                        rules.push(father_rule.clone());
+                    }
+                }
+
+                PlantGenome::mix_scalars(mother, father, rules, rng)
+            }
+        };
+
+        child.rules.truncate(MAX_GENOME_RULE_COUNT);
+        if child.rules.is_empty() {
+            child.rules = mother.rules.clone();
+        }
+
+        return child;
+    }
+
+    fn mix_scalars<R: Rng>(mother: &PlantGenome, father: &PlantGenome, rules: Vec<GenomeRule>, rng: &mut R) -> PlantGenome {
+        return PlantGenome {
+            min_enegy_for_growth: pick(mother.min_enegy_for_growth, father.min_enegy_for_growth, rng),
+            baby_energy: pick(mother.baby_energy, father.baby_energy, rng),
+            lifespan: pick(mother.lifespan, father.lifespan, rng),
+            pollination_range: pick(mother.pollination_range, father.pollination_range, rng),
+
+            sapling_strength: pick(mother.sapling_strength, father.sapling_strength, rng),
+            sapling_photoreceptiveness: pick(mother.sapling_photoreceptiveness, father.sapling_photoreceptiveness, rng),
+            sapling_water_intake: pick(mother.sapling_water_intake, father.sapling_water_intake, rng),
+            sapling_length: pick(mother.sapling_length, father.sapling_length, rng),
+
+            max_offshoots: pick(mother.max_offshoots, father.max_offshoots, rng),
+            offshoot_pitch: pick(mother.offshoot_pitch, father.offshoot_pitch, rng),
+            phyllotaxis: if rng.random_bool(0.5) { mother.phyllotaxis } else { father.phyllotaxis },
+
+            rules
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plant::genome_validation::SCALAR_GENES;
+    use crate::world::WorldRng;
+
+    fn full_parent(rng: &mut WorldRng) -> PlantGenome {
+        //Parents with as many rules as allowed, so any strategy that keeps too many of them has to be cut back
+        let mut genome = PlantGenome::random(rng);
+        while genome.rules.len() < MAX_GENOME_RULE_COUNT {
+            genome.rules.push(GenomeRule::random(rng));
+        }
+        return genome;
+    }
+
+    fn assert_child_of(child: &PlantGenome, mother: &PlantGenome, father: &PlantGenome) {
+        assert!((1 ..= MAX_GENOME_RULE_COUNT).contains(&child.rules.len()), "{}", child.to_text());
+        for (name, _) in SCALAR_GENES {
+            let val = child.scalar_gene(name).unwrap();
+            assert!(val == mother.scalar_gene(name).unwrap() || val == father.scalar_gene(name).unwrap(), "`{}` came from neither parent", name);
+        }
+        assert!(child.phyllotaxis == mother.phyllotaxis || child.phyllotaxis == father.phyllotaxis);
+        for rule in &child.rules {
+            assert!(mother.rules.contains(rule) || father.rules.contains(rule), "{}", child.to_text());
+        }
+    }
+
+    fn check_strategy(strategy: CrossoverStrategy) {
+        for seed in 0..200 {
+            let mut rng = WorldRng::seed_from_u64(seed);
+            let mother = full_parent(&mut rng);
+            let father = full_parent(&mut rng);
+
+            let child = PlantGenome::crossover(&mother, &father, strategy, &mut rng);
+            assert_child_of(&child, &mother, &father);
+
+            //A genome crossed with itself has nothing else to take from, so it has to come back unchanged
+            let child = PlantGenome::crossover(&mother, &mother, strategy, &mut rng);
+            assert!(child == mother, "{}", child.to_text());
+        }
+    }
+
+    #[test]
+    fn single_point_child_comes_from_parents() {
+        check_strategy(CrossoverStrategy::SinglePoint);
+    }
+
+    #[test]
+    fn uniform_child_comes_from_parents() {
+        check_strategy(CrossoverStrategy::Uniform);
+    }
+
+    #[test]
+    fn rule_aligned_child_comes_from_parents() {
+        check_strategy(CrossoverStrategy::RuleAligned);
+    }
+}
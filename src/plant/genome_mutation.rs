@@ -0,0 +1,379 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::plant::hormone::Hormone;
+use crate::plant::branch::BRANCH_MAX_OFFSHOOTS;
+use crate::plant::genome::{PlantGenome, GenomeRule, RuleReq, RuleCondition, RuleOutcome, OffshootSelection, Phyllotaxis, MAX_GENOME_RULE_COUNT, MAX_RULE_CONDITION_SIZE};
+
+#[derive(Clone, Copy)]
+pub struct MutationRates {
+    // Per numeric field: the chance of it being jittered, and how far (as a fraction of its typical range) it can move
+    pub numeric_jitter_chance: f32,
+    pub numeric_jitter_scale: f32,
+
+    // Per rule
+    pub swap_req_chance: f32,
+    pub change_outcome_chance: f32,
+    pub combine_condition_chance: f32,      // ANDs or ORs another requirement test onto the condition
+    pub drop_condition_chance: f32,         // Drops one term from a compound condition
+    pub negate_condition_chance: f32,       // Adds or removes a NOT around the condition
+
+    // Per genome
+    pub insert_rule_chance: f32,
+    pub delete_rule_chance: f32,
+    pub reorder_rule_chance: f32,
+    pub change_phyllotaxis_chance: f32
+}
+
+impl MutationRates {
+    pub fn new() -> MutationRates {
+        return MutationRates {
+            numeric_jitter_chance: 0.1,
+            numeric_jitter_scale: 0.1,
+
+            swap_req_chance: 0.02,
+            change_outcome_chance: 0.02,
+            combine_condition_chance: 0.01,
+            drop_condition_chance: 0.01,
+            negate_condition_chance: 0.005,
+
+            insert_rule_chance: 0.03,
+            delete_rule_chance: 0.03,
+            reorder_rule_chance: 0.03,
+            change_phyllotaxis_chance: 0.01
+        }
+    }
+
+    pub fn none() -> MutationRates {
+        return MutationRates {
+            numeric_jitter_chance: 0.0,
+            numeric_jitter_scale: 0.0,
+
+            swap_req_chance: 0.0,
+            change_outcome_chance: 0.0,
+            combine_condition_chance: 0.0,
+            drop_condition_chance: 0.0,
+            negate_condition_chance: 0.0,
+
+            insert_rule_chance: 0.0,
+            delete_rule_chance: 0.0,
+            reorder_rule_chance: 0.0,
+            change_phyllotaxis_chance: 0.0
+        }
+    }
+}
+
+// Ranges that mutation keeps each numeric gene and outcome field inside of. Genome files are held to the same ranges when they're loaded
+pub const MIN_ENERGY_FOR_GROWTH_RANGE: (f32, f32) = (0.0, 200.0);
+pub const BABY_ENERGY_RANGE: (f32, f32) = (1.0, 200.0);
+pub const LIFESPAN_RANGE: (f32, f32) = (10.0, 5000.0);                          // In plant ticks
+pub const POLLINATION_DISTANCE_RANGE: (f32, f32) = (0.0, 20.0);                 // In terrain cells
+pub const FRACTION_RANGE: (f32, f32) = (0.0, 1.0);                              // Strengths, photoreceptiveness, water intakes, spreads, placement straightness and hormone amounts
+pub const LENGTH_RANGE: (f32, f32) = (0.05, 1.5);
+pub const MAX_OFFSHOOTS_RANGE: (f32, f32) = (1.0, BRANCH_MAX_OFFSHOOTS as f32);
+pub const OFFSHOOT_PITCH_RANGE: (f32, f32) = (0.0, 1.5);
+pub const PRIORITY_RANGE: (f32, f32) = (0.0, 10.0);
+pub const MODIFY_FACTOR_RANGE: (f32, f32) = (-1.0, 1.0);
+pub const LEAF_AREA_RANGE: (f32, f32) = (0.05, 1.0);
+pub const LEAF_ANGLE_RANGE: (f32, f32) = (0.0, PI);
+pub const LEAF_LIFESPAN_RANGE: (f32, f32) = (10.0, 3000.0);                     // In plant ticks
+
+fn jitter<R: Rng>(val: &mut f32, range: f32, rates: &MutationRates, rng: &mut R) {
+    if rng.random_range(0.0 .. 1.0) < rates.numeric_jitter_chance {
+        *val += rng.random_range(-1.0 .. 1.0) * rates.numeric_jitter_scale * range;
+    }
+}
+
+fn jitter_clamped<R: Rng>(val: &mut f32, range: (f32, f32), rates: &MutationRates, rng: &mut R) {
+    jitter(val, range.1 - range.0, rates, rng);
+    *val = val.clamp(range.0, range.1);
+}
+
+impl PlantGenome {
+    pub fn mutate<R: Rng>(&mut self, rates: &MutationRates, rng: &mut R) {
+        jitter_clamped(&mut self.min_enegy_for_growth, MIN_ENERGY_FOR_GROWTH_RANGE, rates, rng);
+        jitter_clamped(&mut self.baby_energy, BABY_ENERGY_RANGE, rates, rng);
+        jitter_clamped(&mut self.lifespan, LIFESPAN_RANGE, rates, rng);
+        jitter_clamped(&mut self.pollination_range, POLLINATION_DISTANCE_RANGE, rates, rng);
+
+        jitter_clamped(&mut self.sapling_strength, FRACTION_RANGE, rates, rng);
+        jitter_clamped(&mut self.sapling_photoreceptiveness, FRACTION_RANGE, rates, rng);
+        jitter_clamped(&mut self.sapling_water_intake, FRACTION_RANGE, rates, rng);
+        jitter_clamped(&mut self.sapling_length, LENGTH_RANGE, rates, rng);
+
+        jitter_clamped(&mut self.max_offshoots, MAX_OFFSHOOTS_RANGE, rates, rng);
+        jitter_clamped(&mut self.offshoot_pitch, OFFSHOOT_PITCH_RANGE, rates, rng);
+        if rng.random_range(0.0 .. 1.0) < rates.change_phyllotaxis_chance {
+            self.phyllotaxis = Phyllotaxis::random(rng);
+        }
+
+        for rule in &mut self.rules {
+            rule.mutate(rates, rng);
+        }
+
+        if self.rules.len() < MAX_GENOME_RULE_COUNT && rng.random_range(0.0 .. 1.0) < rates.insert_rule_chance {
+            let index = rng.random_range(0 ..= self.rules.len());
+            self.rules.insert(index, GenomeRule::random(rng));
+        }
+
+        if self.rules.len() > 1 && rng.random_range(0.0 .. 1.0) < rates.delete_rule_chance {
+            let index = rng.random_range(0 .. self.rules.len());
+            self.rules.remove(index);
+        }
+
+        if self.rules.len() > 1 && rng.random_range(0.0 .. 1.0) < rates.reorder_rule_chance {
+            // Moves a single rule to a new place in the list, since rule order decides which outcome fires first
+            let from = rng.random_range(0 .. self.rules.len());
+            let rule = self.rules.remove(from);
+            let to = rng.random_range(0 ..= self.rules.len());
+            self.rules.insert(to, rule);
+        }
+    }
+}
+
+impl GenomeRule {
+    pub fn mutate<R: Rng>(&mut self, rates: &MutationRates, rng: &mut R) {
+        self.condition.mutate(rates, rng);
+
+        if rng.random_range(0.0 .. 1.0) < rates.combine_condition_chance {
+            self.condition.combine(rng);
+        }
+        if rng.random_range(0.0 .. 1.0) < rates.drop_condition_chance {
+            self.condition.drop_term(rng);
+        }
+        if rng.random_range(0.0 .. 1.0) < rates.negate_condition_chance {
+            self.condition.negate();
+        }
+
+        if rng.random_range(0.0 .. 1.0) < rates.change_outcome_chance {
+            self.outcome = RuleOutcome::random(rng);
+        } else {
+            self.outcome.mutate(rates, rng);
+        }
+    }
+}
+
+impl RuleCondition {
+    pub fn mutate<R: Rng>(&mut self, rates: &MutationRates, rng: &mut R) {
+        //Jitters every requirement test in the tree, without changing its shape
+        match self {
+            RuleCondition::Req { req, min, max } => {
+                if rng.random_range(0.0 .. 1.0) < rates.swap_req_chance {
+                    *req = RuleReq::random(rng);
+                }
+
+                let (range_min, range_max) = req.typical_range();
+                jitter(min, range_max - range_min, rates, rng);
+                jitter(max, range_max - range_min, rates, rng);
+                if *min > *max {
+                    std::mem::swap(min, max);
+                }
+            },
+            RuleCondition::Not(condition) => condition.mutate(rates, rng),
+            RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
+                for condition in conditions {
+                    condition.mutate(rates, rng);
+                }
+            }
+        }
+    }
+
+    pub fn combine<R: Rng>(&mut self, rng: &mut R) {
+        //Joins a new random requirement test onto the condition with AND or OR, if there's room
+        let is_and = rng.random_bool(0.5);
+        let new_term = RuleCondition::random_req(rng);
+        let size = self.size();
+
+        match self {
+            RuleCondition::And(conditions) if is_and => {
+                if size < MAX_RULE_CONDITION_SIZE {
+                    conditions.push(new_term);
+                }
+            },
+            RuleCondition::Or(conditions) if !is_and => {
+                if size < MAX_RULE_CONDITION_SIZE {
+                    conditions.push(new_term);
+                }
+            },
+            _ => {
+                if size + 2 <= MAX_RULE_CONDITION_SIZE {
+                    let old = std::mem::replace(self, RuleCondition::And(vec![]));
+                    *self = match is_and {
+                        true => RuleCondition::And(vec![old, new_term]),
+                        false => RuleCondition::Or(vec![old, new_term])
+                    };
+                }
+            }
+        }
+    }
+
+    pub fn drop_term<R: Rng>(&mut self, rng: &mut R) {
+        //Removes one term of a top level AND or OR, collapsing it down to the last term once there's only one left
+        match self {
+            RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
+                let index = rng.random_range(0 .. conditions.len());
+                conditions.remove(index);
+                if conditions.len() == 1 {
+                    *self = conditions.remove(0);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    pub fn negate(&mut self) {
+        match self {
+            RuleCondition::Not(condition) => {
+                let inner = std::mem::replace(condition.as_mut(), RuleCondition::And(vec![]));
+                *self = inner;
+            },
+            _ => {
+                if self.size() < MAX_RULE_CONDITION_SIZE {
+                    let old = std::mem::replace(self, RuleCondition::And(vec![]));
+                    *self = RuleCondition::Not(Box::new(old));
+                }
+            }
+        }
+    }
+}
+
+impl RuleOutcome {
+    pub fn mutate<R: Rng>(&mut self, rates: &MutationRates, rng: &mut R) {
+        match self {
+            RuleOutcome::Exit => (),
+            RuleOutcome::KillOffshoot(offshoot_selection) => {
+                if rng.random_range(0.0 .. 1.0) < rates.numeric_jitter_chance {
+                    *offshoot_selection = OffshootSelection::random(rng);
+                }
+            },
+            RuleOutcome::RequestModifyBranch {
+                priority,
+                strength_factor,
+                photoreceptiveness_factor,
+                water_intake_factor,
+                length_factor
+            } => {
+                jitter_clamped(priority, PRIORITY_RANGE, rates, rng);
+                jitter_clamped(strength_factor, MODIFY_FACTOR_RANGE, rates, rng);
+                jitter_clamped(photoreceptiveness_factor, MODIFY_FACTOR_RANGE, rates, rng);
+                jitter_clamped(water_intake_factor, MODIFY_FACTOR_RANGE, rates, rng);
+                jitter_clamped(length_factor, MODIFY_FACTOR_RANGE, rates, rng);
+            },
+            RuleOutcome::RequestNewOffshoot {
+                priority,
+                placement_straightness,
+                strength,
+                photoreceptiveness,
+                water_intake,
+                length
+            } => {
+                jitter_clamped(priority, PRIORITY_RANGE, rates, rng);
+                jitter_clamped(placement_straightness, FRACTION_RANGE, rates, rng);
+                jitter_clamped(strength, FRACTION_RANGE, rates, rng);
+                jitter_clamped(photoreceptiveness, FRACTION_RANGE, rates, rng);
+                jitter_clamped(water_intake, FRACTION_RANGE, rates, rng);
+                jitter_clamped(length, LENGTH_RANGE, rates, rng);
+            },
+            RuleOutcome::RequestNewRoot {
+                priority,
+                spread,
+                water_intake,
+                length
+            } => {
+                jitter_clamped(priority, PRIORITY_RANGE, rates, rng);
+                jitter_clamped(spread, FRACTION_RANGE, rates, rng);
+                jitter_clamped(water_intake, FRACTION_RANGE, rates, rng);
+                jitter_clamped(length, LENGTH_RANGE, rates, rng);
+            },
+            RuleOutcome::KillRoot => (),
+            RuleOutcome::ProduceHormone { hormone, amount } => {
+                if rng.random_range(0.0 .. 1.0) < rates.numeric_jitter_chance {
+                    *hormone = Hormone::random(rng);
+                }
+                jitter_clamped(amount, FRACTION_RANGE, rates, rng);
+            },
+            RuleOutcome::RequestNewLeaf {
+                priority,
+                area,
+                angle,
+                lifespan
+            } => {
+                jitter_clamped(priority, PRIORITY_RANGE, rates, rng);
+                jitter_clamped(area, LEAF_AREA_RANGE, rates, rng);
+                jitter_clamped(angle, LEAF_ANGLE_RANGE, rates, rng);
+                jitter_clamped(lifespan, LEAF_LIFESPAN_RANGE, rates, rng);
+            },
+            RuleOutcome::ShedLeaves => ()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldRng;
+
+    fn heavy_rates() -> MutationRates {
+        //Far above the real rates so every kind of mutation, and every clamp, gets hit many times over
+        return MutationRates {
+            numeric_jitter_chance: 0.5,
+            numeric_jitter_scale: 1.0,
+
+            swap_req_chance: 0.3,
+            change_outcome_chance: 0.3,
+            combine_condition_chance: 0.3,
+            drop_condition_chance: 0.2,
+            negate_condition_chance: 0.2,
+
+            insert_rule_chance: 0.5,
+            delete_rule_chance: 0.3,
+            reorder_rule_chance: 0.3,
+            change_phyllotaxis_chance: 0.3
+        }
+    }
+
+    fn assert_in_bounds(genome: &PlantGenome) {
+        assert!((1 ..= MAX_GENOME_RULE_COUNT).contains(&genome.rules.len()), "{}", genome.to_text());
+        for rule in &genome.rules {
+            assert!(rule.condition.size() <= MAX_RULE_CONDITION_SIZE, "{}", genome.to_text());
+        }
+        match genome.validate() {
+            Ok(()) => (),
+            Err(message) => panic!("{}\n{}", message, genome.to_text())
+        }
+    }
+
+    #[test]
+    fn mutation_keeps_genomes_in_bounds() {
+        let rates = heavy_rates();
+        for seed in 0..20 {
+            let mut rng = WorldRng::seed_from_u64(seed);
+            let mut genome = if seed % 2 == 0 { PlantGenome::random(&mut rng) } else { PlantGenome::human_made_tree_genome() };
+            assert_in_bounds(&genome);
+
+            for _generation in 0..300 {
+                genome.mutate(&rates, &mut rng);
+                assert_in_bounds(&genome);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_child() {
+        for seed in 0..20 {
+            let mut parent_rng = WorldRng::seed_from_u64(seed);
+            let parent = PlantGenome::random(&mut parent_rng);
+
+            let mut a = parent.clone();
+            let mut b = parent.clone();
+            let mut rng_a = WorldRng::seed_from_u64(seed + 100);
+            let mut rng_b = WorldRng::seed_from_u64(seed + 100);
+            for _generation in 0..50 {
+                a.mutate(&heavy_rates(), &mut rng_a);
+                b.mutate(&heavy_rates(), &mut rng_b);
+            }
+            assert!(a == b, "{}\n{}", a.to_text(), b.to_text());
+        }
+    }
+}
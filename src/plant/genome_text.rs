@@ -0,0 +1,729 @@
+// Plain text format for genomes, so they can be written, diffed and shared as files instead of Rust source.
+//
+//     # Comments run to the end of the line
+//     min_enegy_for_growth = 34
+//     baby_energy = 75
+//     ...
+//     phyllotaxis = Spiral
+//     rule BranchDepthReq in [6.5, 10] => Exit
+//     rule BranchDepthReq in [-1, 4.5] => RequestNewOffshoot { priority = 10, placement_straightness = 0.5, ... }
+//     rule PlantBranchReq in [11.5, 99] => KillOffshoot(MostCostlyPerYield)
+//     rule PlantRootReq in [-1, 3.5] => RequestNewRoot { priority = 4, spread = 0.6, water_intake = 0.6, length = 0.4 }
+//     rule BranchDepthReq in [4.5, 10] and not (PlantWaterReq in [0, 30] or TerrainDrynessReq in [0.8, 1]) => Exit
+//     rule BranchAuxinReq in [0, 0.5] => ProduceHormone(Auxin) { amount = 0.3 }
+//     rule BranchLeafReq in [-1, 2.5] => RequestNewLeaf { priority = 8, area = 0.4, angle = 1.2, lifespan = 600 }
+//
+// Conditions combine requirement tests with `not`, `and` and `or`, binding in that order, and can be grouped with brackets.
+// Every scalar gene has to be given exactly once, and every field of an outcome has to be filled in.
+// Numbers have to be finite, and genes and outcome fields have to sit inside the same ranges that mutation keeps them in.
+// to_text prints numbers with their shortest exact representation, so printing then parsing gives back the same genome.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+use crate::plant::hormone::Hormone;
+use crate::plant::genome::{PlantGenome, GenomeRule, RuleReq, RuleCondition, RuleOutcome, OffshootSelection, Phyllotaxis, MAX_RULE_CONDITION_SIZE};
+use crate::plant::genome_validation::{
+    SCALAR_GENES, MODIFY_BRANCH_FIELDS, NEW_OFFSHOOT_FIELDS, NEW_ROOT_FIELDS, PRODUCE_HORMONE_FIELDS, NEW_LEAF_FIELDS, check_value, check_rule_count
+};
+
+pub struct GenomeParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+impl Display for GenomeParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        return write!(f, "{}:{}: {}", self.line, self.column, self.message);
+    }
+}
+
+pub fn rule_req_name(req: &RuleReq) -> &'static str {
+    match req {
+        RuleReq::BranchDepthReq => "BranchDepthReq",
+        RuleReq::BranchStrengthReq => "BranchStrengthReq",
+        RuleReq::BranchPhotoreceptivenessReq => "BranchPhotoreceptivenessReq",
+        RuleReq::BranchWaterIntakeReq => "BranchWaterIntakeReq",
+        RuleReq::BranchLengthReq => "BranchLengthReq",
+        RuleReq::BranchAuxinReq => "BranchAuxinReq",
+        RuleReq::BranchCytokininReq => "BranchCytokininReq",
+        RuleReq::BranchLeafReq => "BranchLeafReq",
+
+        RuleReq::PlantEnergyReq => "PlantEnergyReq",
+        RuleReq::PlantWaterReq => "PlantWaterReq",
+        RuleReq::PlantSunlightReq => "PlantSunlightReq",
+        RuleReq::PlantBranchReq => "PlantBranchReq",
+        RuleReq::PlantRootReq => "PlantRootReq",
+        RuleReq::PlantNitrogenReq => "PlantNitrogenReq",
+        RuleReq::PlantAgeReq => "PlantAgeReq",
+
+        RuleReq::TerrainHeightReq => "TerrainHeightReq",
+        RuleReq::TerrainDrynessReq => "TerrainDrynessReq",
+        RuleReq::TerrainRockinessReq => "TerrainRockinessReq",
+        RuleReq::TerrainWaterlog => "TerrainWaterlog",
+
+        RuleReq::SeasonReq => "SeasonReq"
+    }
+}
+
+fn phyllotaxis_name(phyllotaxis: &Phyllotaxis) -> &'static str {
+    match phyllotaxis {
+        Phyllotaxis::Random => "Random",
+        Phyllotaxis::Spiral => "Spiral",
+        Phyllotaxis::Opposite => "Opposite",
+        Phyllotaxis::Whorled => "Whorled"
+    }
+}
+
+fn offshoot_selection_name(offshoot_selection: &OffshootSelection) -> &'static str {
+    match offshoot_selection {
+        OffshootSelection::One => "One",
+        OffshootSelection::Two => "Two",
+        OffshootSelection::Longest => "Longest",
+        OffshootSelection::Weakest => "Weakest",
+        OffshootSelection::LeastPhotoreceptive => "LeastPhotoreceptive",
+        OffshootSelection::MostCostlyPerYield => "MostCostlyPerYield",
+        OffshootSelection::Random => "Random"
+    }
+}
+
+fn offshoot_selection_from_name(name: &str) -> Option<OffshootSelection> {
+    match name {
+        "One" => Some(OffshootSelection::One),
+        "Two" => Some(OffshootSelection::Two),
+        "Longest" => Some(OffshootSelection::Longest),
+        "Weakest" => Some(OffshootSelection::Weakest),
+        "LeastPhotoreceptive" => Some(OffshootSelection::LeastPhotoreceptive),
+        "MostCostlyPerYield" => Some(OffshootSelection::MostCostlyPerYield),
+        "Random" => Some(OffshootSelection::Random),
+        _ => None
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Printing
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+impl PlantGenome {
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+
+        for (name, _) in SCALAR_GENES {
+            out += &format!("{} = {}\n", name, self.scalar_gene(name).unwrap());
+        }
+        out += &format!("phyllotaxis = {}\n", phyllotaxis_name(&self.phyllotaxis));
+        out += "\n";
+
+        for rule in &self.rules {
+            out += &format!("rule {} => {}\n", rule_condition_to_text(&rule.condition), rule_outcome_to_text(&rule.outcome));
+        }
+
+        return out;
+    }
+}
+
+fn rule_condition_to_text(condition: &RuleCondition) -> String {
+    //ANDs and ORs inside of other conditions always get brackets, so the text parses back into exactly the same tree
+    fn term_to_text(condition: &RuleCondition) -> String {
+        match condition {
+            RuleCondition::And(_) | RuleCondition::Or(_) => format!("({})", rule_condition_to_text(condition)),
+            _ => rule_condition_to_text(condition)
+        }
+    }
+
+    match condition {
+        RuleCondition::Req { req, min, max } => format!("{} in [{}, {}]", rule_req_name(req), min, max),
+        RuleCondition::Not(condition) => format!("not {}", term_to_text(condition)),
+        RuleCondition::And(conditions) => conditions.iter().map(term_to_text).collect::<Vec<String>>().join(" and "),
+        RuleCondition::Or(conditions) => conditions.iter().map(term_to_text).collect::<Vec<String>>().join(" or ")
+    }
+}
+
+fn rule_outcome_to_text(outcome: &RuleOutcome) -> String {
+    match outcome {
+        RuleOutcome::Exit => "Exit".to_string(),
+        RuleOutcome::KillOffshoot(offshoot_selection) => format!("KillOffshoot({})", offshoot_selection_name(offshoot_selection)),
+        RuleOutcome::RequestModifyBranch {
+            priority,
+            strength_factor,
+            photoreceptiveness_factor,
+            water_intake_factor,
+            length_factor
+        } => format!(
+            "RequestModifyBranch {{ priority = {}, strength_factor = {}, photoreceptiveness_factor = {}, water_intake_factor = {}, length_factor = {} }}",
+            priority, strength_factor, photoreceptiveness_factor, water_intake_factor, length_factor
+        ),
+        RuleOutcome::RequestNewOffshoot {
+            priority,
+            placement_straightness,
+            strength,
+            photoreceptiveness,
+            water_intake,
+            length
+        } => format!(
+            "RequestNewOffshoot {{ priority = {}, placement_straightness = {}, strength = {}, photoreceptiveness = {}, water_intake = {}, length = {} }}",
+            priority, placement_straightness, strength, photoreceptiveness, water_intake, length
+        ),
+        RuleOutcome::RequestNewRoot {
+            priority,
+            spread,
+            water_intake,
+            length
+        } => format!(
+            "RequestNewRoot {{ priority = {}, spread = {}, water_intake = {}, length = {} }}",
+            priority, spread, water_intake, length
+        ),
+        RuleOutcome::KillRoot => "KillRoot".to_string(),
+        RuleOutcome::ProduceHormone { hormone, amount } => format!("ProduceHormone({}) {{ amount = {} }}", hormone.name(), amount),
+        RuleOutcome::RequestNewLeaf {
+            priority,
+            area,
+            angle,
+            lifespan
+        } => format!(
+            "RequestNewLeaf {{ priority = {}, area = {}, angle = {}, lifespan = {} }}",
+            priority, area, angle, lifespan
+        ),
+        RuleOutcome::ShedLeaves => "ShedLeaves".to_string()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Tokenizing
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number(f32),
+    Symbol(&'static str),
+    End
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, GenomeParseError> {
+    let mut tokens: Vec<Token> = vec![];
+
+    for (line_index, line_text) in text.lines().enumerate() {
+        let line = line_index + 1;
+        let chars: Vec<char> = line_text.chars().collect();
+        let mut i: usize = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let column = i + 1;
+
+            if c == '#' {
+                break;
+            }
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+
+            if c == '=' && chars.get(i + 1) == Some(&'>') {
+                tokens.push(Token { kind: TokenKind::Symbol("=>"), line, column });
+                i += 2;
+                continue;
+            }
+
+            let symbol = match c {
+                '=' => Some("="),
+                '[' => Some("["),
+                ']' => Some("]"),
+                ',' => Some(","),
+                '{' => Some("{"),
+                '}' => Some("}"),
+                '(' => Some("("),
+                ')' => Some(")"),
+                _ => None
+            };
+            match symbol {
+                Some(symbol) => {
+                    tokens.push(Token { kind: TokenKind::Symbol(symbol), line, column });
+                    i += 1;
+                    continue;
+                }
+                None => ()
+            }
+
+            if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || ((chars[i] == '-' || chars[i] == '+') && (chars[i - 1] == 'e' || chars[i - 1] == 'E'))) {
+                    i += 1;
+                }
+                let number_text: String = chars[start..i].iter().collect();
+                match number_text.parse::<f32>() {
+                    Ok(val) if val.is_finite() => tokens.push(Token { kind: TokenKind::Number(val), line, column }),
+                    Ok(_) => return Err(GenomeParseError { line, column, message: format!("`{}` is not a finite number", number_text) }),
+                    Err(_) => return Err(GenomeParseError { line, column, message: format!("`{}` is not a number", number_text) })
+                }
+                continue;
+            }
+
+            if c.is_ascii_alphabetic() || c == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token { kind: TokenKind::Ident(chars[start..i].iter().collect()), line, column });
+                continue;
+            }
+
+            return Err(GenomeParseError { line, column, message: format!("Unexpected character `{}`", c) });
+        }
+    }
+
+    let line = text.lines().count().max(1);
+    let column = text.lines().last().map(|last_line| last_line.chars().count() + 1).unwrap_or(1);
+    tokens.push(Token { kind: TokenKind::End, line, column });
+
+    return Ok(tokens);
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Parsing
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize        // How many `not`s and brackets the condition being parsed is currently inside of
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        return &self.tokens[self.position];
+    }
+
+    fn next(&mut self) -> &Token {
+        let token = &self.tokens[self.position];
+        if self.position + 1 < self.tokens.len() {
+            self.position += 1;
+        }
+        return token;
+    }
+
+    fn error_at(token: &Token, message: String) -> GenomeParseError {
+        return GenomeParseError { line: token.line, column: token.column, message };
+    }
+
+    fn describe(token: &Token) -> String {
+        match &token.kind {
+            TokenKind::Ident(name) => format!("`{}`", name),
+            TokenKind::Number(val) => format!("`{}`", val),
+            TokenKind::Symbol(symbol) => format!("`{}`", symbol),
+            TokenKind::End => "the end of the file".to_string()
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<(), GenomeParseError> {
+        let token = self.next();
+        if token.kind == TokenKind::Symbol(symbol) {
+            return Ok(());
+        }
+        return Err(Self::error_at(token, format!("Expected `{}` but found {}", symbol, Self::describe(token))));
+    }
+
+    fn expect_number(&mut self) -> Result<f32, GenomeParseError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Number(val) => Ok(val),
+            _ => Err(Self::error_at(token, format!("Expected a number but found {}", Self::describe(token))))
+        }
+    }
+
+    fn expect_number_in_range(&mut self, name: &str, range: (f32, f32)) -> Result<f32, GenomeParseError> {
+        let val = self.expect_number()?;
+        return match check_value(name, val, range) {
+            Ok(()) => Ok(val),
+            Err(message) => Err(Self::error_at(&self.tokens[self.position - 1], message))
+        };
+    }
+
+    fn expect_ident(&mut self) -> Result<(String, usize, usize), GenomeParseError> {
+        let token = self.next();
+        match &token.kind {
+            TokenKind::Ident(name) => Ok((name.clone(), token.line, token.column)),
+            _ => Err(Self::error_at(token, format!("Expected a name but found {}", Self::describe(token))))
+        }
+    }
+
+    fn parse_genome(&mut self) -> Result<PlantGenome, GenomeParseError> {
+        let mut genome = PlantGenome {
+            min_enegy_for_growth: 0.0,
+            baby_energy: 0.0,
+            lifespan: 0.0,
+            pollination_range: 0.0,
+
+            sapling_strength: 0.0,
+            sapling_photoreceptiveness: 0.0,
+            sapling_water_intake: 0.0,
+            sapling_length: 0.0,
+
+            max_offshoots: 0.0,
+            offshoot_pitch: 0.0,
+            phyllotaxis: Phyllotaxis::Random,
+
+            rules: vec![]
+        };
+        let mut genes_set: Vec<&str> = vec![];
+        let mut phyllotaxis_set = false;
+
+        loop {
+            if self.peek().kind == TokenKind::End {
+                break;
+            }
+
+            let (name, line, column) = self.expect_ident()?;
+
+            if name == "rule" {
+                if let Err(message) = check_rule_count(genome.rules.len() + 1) {
+                    return Err(GenomeParseError { line, column, message });
+                }
+                genome.rules.push(self.parse_rule()?);
+                continue;
+            }
+
+            if name == "phyllotaxis" {
+                if phyllotaxis_set {
+                    return Err(GenomeParseError { line, column, message: "Gene `phyllotaxis` is set more than once".to_string() });
+                }
+                self.expect_symbol("=")?;
+                let (phyllotaxis_text, line, column) = self.expect_ident()?;
+                genome.phyllotaxis = match Phyllotaxis::ALL.iter().find(|phyllotaxis| phyllotaxis_name(phyllotaxis) == phyllotaxis_text) {
+                    Some(phyllotaxis) => *phyllotaxis,
+                    None => return Err(GenomeParseError { line, column, message: format!("Unknown phyllotaxis `{}`", phyllotaxis_text) })
+                };
+                phyllotaxis_set = true;
+                continue;
+            }
+
+            let (gene_name, range) = match SCALAR_GENES.iter().find(|(gene_name, _)| *gene_name == name) {
+                Some(gene) => *gene,
+                None => return Err(GenomeParseError { line, column, message: format!("Unknown gene `{}`", name) })
+            };
+            if genes_set.contains(&gene_name) {
+                return Err(GenomeParseError { line, column, message: format!("Gene `{}` is set more than once", name) });
+            }
+
+            self.expect_symbol("=")?;
+            *genome.scalar_gene_mut(gene_name).unwrap() = self.expect_number_in_range(gene_name, range)?;
+            genes_set.push(gene_name);
+        }
+
+        for (gene_name, _) in SCALAR_GENES {
+            if !genes_set.contains(&gene_name) {
+                return Err(Self::error_at(self.peek(), format!("Missing gene `{}`", gene_name)));
+            }
+        }
+        if !phyllotaxis_set {
+            return Err(Self::error_at(self.peek(), "Missing gene `phyllotaxis`".to_string()));
+        }
+        if let Err(message) = check_rule_count(genome.rules.len()) {
+            return Err(Self::error_at(self.peek(), message));
+        }
+
+        return Ok(genome);
+    }
+
+    fn parse_rule(&mut self) -> Result<GenomeRule, GenomeParseError> {
+        let (line, column) = (self.peek().line, self.peek().column);
+        let condition = self.parse_or()?;
+        if let Err(message) = condition.validate() {
+            return Err(GenomeParseError { line, column, message });
+        }
+        self.expect_symbol("=>")?;
+
+        return Ok(GenomeRule { condition, outcome: self.parse_outcome()? });
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match &self.peek().kind {
+            TokenKind::Ident(name) => name == keyword,
+            _ => false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<RuleCondition, GenomeParseError> {
+        let mut terms: Vec<RuleCondition> = vec![self.parse_and()?];
+        while self.peek_keyword("or") {
+            self.next();
+            terms.push(self.parse_and()?);
+        }
+        return Ok(match terms.len() {
+            1 => terms.remove(0),
+            _ => RuleCondition::Or(terms)
+        });
+    }
+
+    fn parse_and(&mut self) -> Result<RuleCondition, GenomeParseError> {
+        let mut terms: Vec<RuleCondition> = vec![self.parse_unary()?];
+        while self.peek_keyword("and") {
+            self.next();
+            terms.push(self.parse_unary()?);
+        }
+        return Ok(match terms.len() {
+            1 => terms.remove(0),
+            _ => RuleCondition::And(terms)
+        });
+    }
+
+    fn parse_unary(&mut self) -> Result<RuleCondition, GenomeParseError> {
+        if self.peek_keyword("not") {
+            self.enter_nesting()?;
+            let condition = RuleCondition::Not(Box::new(self.parse_unary()?));
+            self.depth -= 1;
+            return Ok(condition);
+        }
+        if self.peek().kind == TokenKind::Symbol("(") {
+            self.enter_nesting()?;
+            let condition = self.parse_or()?;
+            self.expect_symbol(")")?;
+            self.depth -= 1;
+            return Ok(condition);
+        }
+        return self.parse_req();
+    }
+
+    fn enter_nesting(&mut self) -> Result<(), GenomeParseError> {
+        //Caps how deep `not`s and brackets can go, so deeply nested input errors out instead of overflowing the stack
+        if self.depth >= MAX_RULE_CONDITION_SIZE {
+            return Err(Self::error_at(self.peek(), format!("Rule conditions can be nested at most {} deep", MAX_RULE_CONDITION_SIZE)));
+        }
+        self.next();
+        self.depth += 1;
+        return Ok(());
+    }
+
+    fn parse_req(&mut self) -> Result<RuleCondition, GenomeParseError> {
+        let (req_name, line, column) = self.expect_ident()?;
+        let req = match RuleReq::ALL.iter().find(|req| rule_req_name(req) == req_name) {
+            Some(req) => *req,
+            None => return Err(GenomeParseError { line, column, message: format!("Unknown rule requirement `{}`", req_name) })
+        };
+
+        let (in_keyword, line, column) = self.expect_ident()?;
+        if in_keyword != "in" {
+            return Err(GenomeParseError { line, column, message: format!("Expected `in` but found `{}`", in_keyword) });
+        }
+
+        self.expect_symbol("[")?;
+        let min = self.expect_number()?;
+        self.expect_symbol(",")?;
+        let max = self.expect_number()?;
+        self.expect_symbol("]")?;
+
+        return Ok(RuleCondition::Req { req, min, max });
+    }
+
+    fn parse_outcome(&mut self) -> Result<RuleOutcome, GenomeParseError> {
+        let (outcome_name, line, column) = self.expect_ident()?;
+
+        match outcome_name.as_str() {
+            "Exit" => Ok(RuleOutcome::Exit),
+            "KillRoot" => Ok(RuleOutcome::KillRoot),
+            "ShedLeaves" => Ok(RuleOutcome::ShedLeaves),
+            "KillOffshoot" => {
+                self.expect_symbol("(")?;
+                let (selection_name, line, column) = self.expect_ident()?;
+                let offshoot_selection = match offshoot_selection_from_name(&selection_name) {
+                    Some(offshoot_selection) => offshoot_selection,
+                    None => return Err(GenomeParseError { line, column, message: format!("Unknown offshoot selection `{}`", selection_name) })
+                };
+                self.expect_symbol(")")?;
+                Ok(RuleOutcome::KillOffshoot(offshoot_selection))
+            },
+            "ProduceHormone" => {
+                self.expect_symbol("(")?;
+                let (hormone_name, line, column) = self.expect_ident()?;
+                let hormone = match Hormone::ALL.iter().find(|hormone| hormone.name() == hormone_name) {
+                    Some(hormone) => *hormone,
+                    None => return Err(GenomeParseError { line, column, message: format!("Unknown hormone `{}`", hormone_name) })
+                };
+                self.expect_symbol(")")?;
+                let fields = self.parse_fields(&PRODUCE_HORMONE_FIELDS)?;
+                Ok(RuleOutcome::ProduceHormone { hormone, amount: fields[0] })
+            },
+            "RequestModifyBranch" => {
+                let fields = self.parse_fields(&MODIFY_BRANCH_FIELDS)?;
+                Ok(RuleOutcome::RequestModifyBranch {
+                    priority: fields[0],
+                    strength_factor: fields[1],
+                    photoreceptiveness_factor: fields[2],
+                    water_intake_factor: fields[3],
+                    length_factor: fields[4]
+                })
+            },
+            "RequestNewOffshoot" => {
+                let fields = self.parse_fields(&NEW_OFFSHOOT_FIELDS)?;
+                Ok(RuleOutcome::RequestNewOffshoot {
+                    priority: fields[0],
+                    placement_straightness: fields[1],
+                    strength: fields[2],
+                    photoreceptiveness: fields[3],
+                    water_intake: fields[4],
+                    length: fields[5]
+                })
+            },
+            "RequestNewRoot" => {
+                let fields = self.parse_fields(&NEW_ROOT_FIELDS)?;
+                Ok(RuleOutcome::RequestNewRoot {
+                    priority: fields[0],
+                    spread: fields[1],
+                    water_intake: fields[2],
+                    length: fields[3]
+                })
+            },
+            "RequestNewLeaf" => {
+                let fields = self.parse_fields(&NEW_LEAF_FIELDS)?;
+                Ok(RuleOutcome::RequestNewLeaf {
+                    priority: fields[0],
+                    area: fields[1],
+                    angle: fields[2],
+                    lifespan: fields[3]
+                })
+            },
+            _ => Err(GenomeParseError { line, column, message: format!("Unknown rule outcome `{}`", outcome_name) })
+        }
+    }
+
+    fn parse_fields(&mut self, fields: &[(&str, (f32, f32))]) -> Result<Vec<f32>, GenomeParseError> {
+        //Parses `{ name = number, ... }` where every named field shows up exactly once, in any order, with a number inside its range
+        let mut vals: Vec<Option<f32>> = vec![None; fields.len()];
+
+        self.expect_symbol("{")?;
+        loop {
+            if self.peek().kind == TokenKind::Symbol("}") {
+                self.next();
+                break;
+            }
+
+            let (field_name, line, column) = self.expect_ident()?;
+            let field_index = match fields.iter().position(|(name, _)| *name == field_name) {
+                Some(field_index) => field_index,
+                None => return Err(GenomeParseError { line, column, message: format!("Unknown field `{}`", field_name) })
+            };
+            if vals[field_index].is_some() {
+                return Err(GenomeParseError { line, column, message: format!("Field `{}` is set more than once", field_name) });
+            }
+
+            self.expect_symbol("=")?;
+            let (name, range) = fields[field_index];
+            vals[field_index] = Some(self.expect_number_in_range(name, range)?);
+
+            if self.peek().kind == TokenKind::Symbol(",") {
+                self.next();
+            }
+        }
+
+        let mut out: Vec<f32> = Vec::with_capacity(fields.len());
+        for (field_index, val) in vals.iter().enumerate() {
+            match val {
+                Some(val) => out.push(*val),
+                None => return Err(Self::error_at(&self.tokens[self.position - 1], format!("Missing field `{}`", fields[field_index].0)))
+            }
+        }
+        return Ok(out);
+    }
+}
+
+impl PlantGenome {
+    pub fn from_text(text: &str) -> Result<PlantGenome, GenomeParseError> {
+        let mut parser = Parser { tokens: tokenize(text)?, position: 0, depth: 0 };
+        return parser.parse_genome();
+    }
+
+    pub fn load_from_file(path: &str) -> Result<PlantGenome, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => return Err(format!("{}: {}", path, error))
+        };
+        return PlantGenome::from_text(&text).map_err(|error| format!("{}:{}", path, error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trips(genome: &PlantGenome) {
+        let text = genome.to_text();
+        match PlantGenome::from_text(&text) {
+            Ok(parsed) => assert!(parsed == *genome, "Parsed genome differs from the printed one:\n{}\nreprinted as:\n{}", text, parsed.to_text()),
+            Err(error) => panic!("Printed genome failed to parse at {}\n{}", error, text)
+        }
+    }
+
+    #[test]
+    fn human_made_tree_round_trips() {
+        assert_round_trips(&PlantGenome::human_made_tree_genome());
+    }
+
+    #[test]
+    fn nested_condition_round_trips() {
+        let mut genome = PlantGenome::human_made_tree_genome();
+        genome.rules[0].condition = RuleCondition::And(vec![
+            RuleCondition::Req { req: RuleReq::BranchDepthReq, min: 4.5, max: 10.0 },
+            RuleCondition::Not(Box::new(RuleCondition::Or(vec![
+                RuleCondition::Req { req: RuleReq::PlantWaterReq, min: 0.0, max: 30.0 },
+                RuleCondition::Not(Box::new(RuleCondition::Req { req: RuleReq::TerrainDrynessReq, min: 0.8, max: 1.0 }))
+            ])))
+        ]);
+        assert!(genome.rules[0].condition.size() <= MAX_RULE_CONDITION_SIZE);
+        assert_round_trips(&genome);
+    }
+
+    #[test]
+    fn genome_file_matches_human_made_tree() {
+        let text = fs::read_to_string("genomes/human_made_tree.genome").unwrap();
+        assert!(text == PlantGenome::human_made_tree_genome().to_text());
+    }
+
+    #[test]
+    fn rejects_non_finite_and_out_of_range_numbers() {
+        //Parsing stops at the first bad number, so these don't need to be whole genomes
+        for (text, line, column) in [
+            ("lifespan = +nan", 1, 12),
+            ("lifespan = -inf", 1, 12),
+            ("lifespan = 1e40", 1, 12),
+            ("lifespan = 1", 1, 12),
+            ("rule BranchDepthReq in [0, 1] => RequestNewLeaf {\n    area = -0.4\n}", 2, 12)
+        ] {
+            match PlantGenome::from_text(text) {
+                Ok(_) => panic!("`{}` should not parse", text),
+                Err(error) => assert!(error.line == line && error.column == column, "`{}` gave {}", text, error)
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        //The first `not` or bracket past the limit is the one that gets reported
+        for (text, column) in [
+            (format!("rule {}BranchDepthReq in [0, 1] => Exit", "not ".repeat(8)), 34),
+            (format!("rule {}BranchDepthReq in [0, 1] => Exit", "(".repeat(8)), 13),
+            (format!("rule {}BranchDepthReq in [0, 1] => Exit", "(".repeat(100000)), 13)
+        ] {
+            match PlantGenome::from_text(&text) {
+                Ok(_) => panic!("Nesting past the limit should not parse"),
+                Err(error) => assert!(error.line == 1 && error.column == column, "{}", error)
+            }
+        }
+
+        let mut genome = PlantGenome::human_made_tree_genome();
+        genome.rules.truncate(1);
+        let text = genome.to_text().replacen("rule ", "rule not not not not not not BranchDepthReq in [0, 1] => Exit\nrule ", 1);
+        match PlantGenome::from_text(&text) {
+            Ok(genome) => assert!(genome.rules[0].condition.size() == MAX_RULE_CONDITION_SIZE),
+            Err(error) => panic!("Nesting up to the limit should parse but gave {}", error)
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::plant::genome::{PlantGenome, RuleCondition, RuleOutcome, MAX_GENOME_RULE_COUNT, MAX_RULE_CONDITION_SIZE};
+use crate::plant::genome_mutation::{
+    MIN_ENERGY_FOR_GROWTH_RANGE, BABY_ENERGY_RANGE, LIFESPAN_RANGE, POLLINATION_DISTANCE_RANGE, FRACTION_RANGE, LENGTH_RANGE, MAX_OFFSHOOTS_RANGE,
+    OFFSHOOT_PITCH_RANGE, PRIORITY_RANGE, MODIFY_FACTOR_RANGE, LEAF_AREA_RANGE, LEAF_ANGLE_RANGE, LEAF_LIFESPAN_RANGE
+};
+
+// Checks for genomes that come from outside the simulation (genome files and snapshots), holding them to the same limits
+// that random generation and mutation keep every genome inside of, so nothing loaded can panic or break the energy balance
+
+pub const SCALAR_GENES: [(&str, (f32, f32)); 10] = [
+    ("min_enegy_for_growth", MIN_ENERGY_FOR_GROWTH_RANGE),
+    ("baby_energy", BABY_ENERGY_RANGE),
+    ("lifespan", LIFESPAN_RANGE),
+    ("pollination_range", POLLINATION_DISTANCE_RANGE),
+
+    ("sapling_strength", FRACTION_RANGE),
+    ("sapling_photoreceptiveness", FRACTION_RANGE),
+    ("sapling_water_intake", FRACTION_RANGE),
+    ("sapling_length", LENGTH_RANGE),
+
+    ("max_offshoots", MAX_OFFSHOOTS_RANGE),
+    ("offshoot_pitch", OFFSHOOT_PITCH_RANGE)
+];
+
+//Numeric fields of each outcome, in the order they're declared in RuleOutcome
+pub const MODIFY_BRANCH_FIELDS: [(&str, (f32, f32)); 5] = [
+    ("priority", PRIORITY_RANGE),
+    ("strength_factor", MODIFY_FACTOR_RANGE),
+    ("photoreceptiveness_factor", MODIFY_FACTOR_RANGE),
+    ("water_intake_factor", MODIFY_FACTOR_RANGE),
+    ("length_factor", MODIFY_FACTOR_RANGE)
+];
+
+pub const NEW_OFFSHOOT_FIELDS: [(&str, (f32, f32)); 6] = [
+    ("priority", PRIORITY_RANGE),
+    ("placement_straightness", FRACTION_RANGE),
+    ("strength", FRACTION_RANGE),
+    ("photoreceptiveness", FRACTION_RANGE),
+    ("water_intake", FRACTION_RANGE),
+    ("length", LENGTH_RANGE)
+];
+
+pub const NEW_ROOT_FIELDS: [(&str, (f32, f32)); 4] = [
+    ("priority", PRIORITY_RANGE),
+    ("spread", FRACTION_RANGE),
+    ("water_intake", FRACTION_RANGE),
+    ("length", LENGTH_RANGE)
+];
+
+pub const PRODUCE_HORMONE_FIELDS: [(&str, (f32, f32)); 1] = [
+    ("amount", FRACTION_RANGE)
+];
+
+pub const NEW_LEAF_FIELDS: [(&str, (f32, f32)); 4] = [
+    ("priority", PRIORITY_RANGE),
+    ("area", LEAF_AREA_RANGE),
+    ("angle", LEAF_ANGLE_RANGE),
+    ("lifespan", LEAF_LIFESPAN_RANGE)
+];
+
+pub fn check_value(name: &str, val: f32, range: (f32, f32)) -> Result<(), String> {
+    if !val.is_finite() {
+        return Err(format!("`{}` has to be a finite number but is {}", name, val));
+    }
+    if val < range.0 || val > range.1 {
+        return Err(format!("`{}` has to be in [{}, {}] but is {}", name, range.0, range.1, val));
+    }
+    return Ok(());
+}
+
+pub fn check_rule_count(rule_count: usize) -> Result<(), String> {
+    if rule_count == 0 {
+        return Err("Genomes need at least one rule".to_string());
+    }
+    if rule_count > MAX_GENOME_RULE_COUNT {
+        return Err(format!("Genomes can have at most {} rules", MAX_GENOME_RULE_COUNT));
+    }
+    return Ok(());
+}
+
+impl PlantGenome {
+    pub fn scalar_gene(&self, name: &str) -> Option<f32> {
+        match name {
+            "min_enegy_for_growth" => Some(self.min_enegy_for_growth),
+            "baby_energy" => Some(self.baby_energy),
+            "lifespan" => Some(self.lifespan),
+            "pollination_range" => Some(self.pollination_range),
+
+            "sapling_strength" => Some(self.sapling_strength),
+            "sapling_photoreceptiveness" => Some(self.sapling_photoreceptiveness),
+            "sapling_water_intake" => Some(self.sapling_water_intake),
+            "sapling_length" => Some(self.sapling_length),
+
+            "max_offshoots" => Some(self.max_offshoots),
+            "offshoot_pitch" => Some(self.offshoot_pitch),
+
+            _ => None
+        }
+    }
+
+    pub fn scalar_gene_mut(&mut self, name: &str) -> Option<&mut f32> {
+        match name {
+            "min_enegy_for_growth" => Some(&mut self.min_enegy_for_growth),
+            "baby_energy" => Some(&mut self.baby_energy),
+            "lifespan" => Some(&mut self.lifespan),
+            "pollination_range" => Some(&mut self.pollination_range),
+
+            "sapling_strength" => Some(&mut self.sapling_strength),
+            "sapling_photoreceptiveness" => Some(&mut self.sapling_photoreceptiveness),
+            "sapling_water_intake" => Some(&mut self.sapling_water_intake),
+            "sapling_length" => Some(&mut self.sapling_length),
+
+            "max_offshoots" => Some(&mut self.max_offshoots),
+            "offshoot_pitch" => Some(&mut self.offshoot_pitch),
+
+            _ => None
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        check_rule_count(self.rules.len())?;
+        for (name, range) in SCALAR_GENES {
+            check_value(name, self.scalar_gene(name).unwrap(), range)?;
+        }
+        for rule in &self.rules {
+            rule.condition.validate()?;
+            rule.outcome.validate()?;
+        }
+        return Ok(());
+    }
+}
+
+impl RuleCondition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.size() > MAX_RULE_CONDITION_SIZE {
+            return Err(format!("Rule conditions can have at most {} parts", MAX_RULE_CONDITION_SIZE));
+        }
+        return self.validate_terms();
+    }
+
+    fn validate_terms(&self) -> Result<(), String> {
+        match self {
+            RuleCondition::Req { min, max, .. } => {
+                check_value("min", *min, (f32::MIN, f32::MAX))?;
+                check_value("max", *max, (f32::MIN, f32::MAX))?;
+            },
+            RuleCondition::Not(condition) => condition.validate_terms()?,
+            RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
+                if conditions.len() < 2 {
+                    return Err("AND and OR conditions need at least two terms".to_string());
+                }
+                for condition in conditions {
+                    condition.validate_terms()?;
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl RuleOutcome {
+    pub fn validate(&self) -> Result<(), String> {
+        let (fields, vals) = match self {
+            RuleOutcome::RequestModifyBranch { priority, strength_factor, photoreceptiveness_factor, water_intake_factor, length_factor } =>
+                (&MODIFY_BRANCH_FIELDS[..], vec![*priority, *strength_factor, *photoreceptiveness_factor, *water_intake_factor, *length_factor]),
+            RuleOutcome::RequestNewOffshoot { priority, placement_straightness, strength, photoreceptiveness, water_intake, length } =>
+                (&NEW_OFFSHOOT_FIELDS[..], vec![*priority, *placement_straightness, *strength, *photoreceptiveness, *water_intake, *length]),
+            RuleOutcome::RequestNewRoot { priority, spread, water_intake, length } => (&NEW_ROOT_FIELDS[..], vec![*priority, *spread, *water_intake, *length]),
+            RuleOutcome::ProduceHormone { amount, .. } => (&PRODUCE_HORMONE_FIELDS[..], vec![*amount]),
+            RuleOutcome::RequestNewLeaf { priority, area, angle, lifespan } => (&NEW_LEAF_FIELDS[..], vec![*priority, *area, *angle, *lifespan]),
+            RuleOutcome::Exit | RuleOutcome::KillOffshoot(_) | RuleOutcome::KillRoot | RuleOutcome::ShedLeaves => return Ok(())
+        };
+
+        for ((name, range), val) in fields.iter().zip(vals) {
+            check_value(name, val, *range)?;
+        }
+        return Ok(());
+    }
+}
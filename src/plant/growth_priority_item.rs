@@ -3,6 +3,13 @@ use std::cmp::Ordering;
 use crate::plant::branch::Branch;
 use crate::plant::Plant;
 use crate::plant::branch::BranchConnection;
+use crate::plant::root::Root;
+use crate::plant::leaf::Leaf;
+use crate::plant::plant::PLANT_MAX_ROOT_COUNT;
+use crate::terrain::nutrients::NITROGEN_PER_COST;
+
+use rand::Rng;
+use crate::world::WorldRng;
 
 pub fn modify_self_property_helper(original_val: f32, change_factor: f32) -> f32 {
     //Original val must be between 0.0 and 1.0, change factor changes this - positive go up, negative go down, kinda lmao its not a science idk go graph it yourself its weird
@@ -28,9 +35,23 @@ pub struct ModifyBranchPriorityItem {
     pub length_factor: f32
 }
 
+pub struct NewRootPriorityItem {
+    pub spread: f32,
+    pub water_intake: f32,
+    pub length: f32
+}
+
+pub struct NewLeafPriorityItem {
+    pub area: f32,
+    pub angle: f32,
+    pub lifespan: f32
+}
+
 pub enum PriorityItemType {
     NewOffshoot(NewOffshootPriorityItem),
-    ModifyBranch(ModifyBranchPriorityItem)
+    ModifyBranch(ModifyBranchPriorityItem),
+    NewRoot(NewRootPriorityItem),
+    NewLeaf(NewLeafPriorityItem)
 }
 
 pub struct GrowthPriorityItem {
@@ -43,8 +64,6 @@ pub struct GrowthPriorityItem {
 
 impl GrowthPriorityItem {
     pub fn new_offshoot_request(branch_index: usize, placement_straightness: f32, strength: f32, photoreceptiveness: f32, water_intake: f32, length: f32, priority: f32) -> GrowthPriorityItem {
-        let cost = Branch::calculate_cost_from_individual_parts(strength, photoreceptiveness, water_intake, length);
-        
         return GrowthPriorityItem { 
             priority,
 
@@ -78,25 +97,63 @@ impl GrowthPriorityItem {
             ),
         }
     }
+
+    pub fn new_root_request(branch_index: usize, spread: f32, water_intake: f32, length: f32, priority: f32) -> GrowthPriorityItem {
+        return GrowthPriorityItem { 
+            priority,
+            
+            branch_index,
+
+            item: PriorityItemType::NewRoot( 
+                NewRootPriorityItem {
+                    spread, 
+                    water_intake, 
+                    length 
+                }
+            ),
+        }
+    }
+
+    pub fn new_leaf_request(branch_index: usize, area: f32, angle: f32, lifespan: f32, priority: f32) -> GrowthPriorityItem {
+        return GrowthPriorityItem { 
+            priority,
+            
+            branch_index,
+
+            item: PriorityItemType::NewLeaf( 
+                NewLeafPriorityItem {
+                    area, 
+                    angle, 
+                    lifespan 
+                }
+            ),
+        }
+    }
 }
 
 impl Plant {
-    pub fn execute_growth_priority_item(&mut self, growth_priority_item: &GrowthPriorityItem) -> bool {
+    pub fn execute_growth_priority_item(&mut self, growth_priority_item: &GrowthPriorityItem, rng: &mut WorldRng) -> bool {
         match &growth_priority_item.item {
             PriorityItemType::NewOffshoot(item) => {
+                //Other requests in the same tick may have filled the branch up since this one was queued
+                if self.branches[growth_priority_item.branch_index].offshoots.len() >= self.genome.max_offshoot_count() {
+                    return true;
+                }
+
                 let cost = Branch::calculate_cost_from_individual_parts(item.strength, item.photoreceptiveness, item.water_intake, item.length);
 
-                if self.current_energy - cost < self.genome.min_enegy_for_growth {
+                if self.current_energy - cost < self.genome.min_enegy_for_growth || self.current_nitrogen < cost * NITROGEN_PER_COST {
                     return false;
                 }
 
                 let new_index: usize = self.branches.len();
-                let new_offshoot = BranchConnection::new(&item, new_index);
+                let new_offshoot = BranchConnection::new(item, new_index, &self.branches[growth_priority_item.branch_index].offshoots, &self.genome, rng);
                 
-                self.branches.push(Branch::from(item));
+                self.branches.push(Branch::from_new_offshoot_item(item, rng));
                 self.branches[growth_priority_item.branch_index].add_offshoot(new_offshoot);
 
                 self.current_energy -= cost;
+                self.current_nitrogen -= cost * NITROGEN_PER_COST;
             }
             PriorityItemType::ModifyBranch(item) => {
                 let new_strength = modify_self_property_helper(self.branches[growth_priority_item.branch_index].strength, item.strength_factor);
@@ -108,7 +165,7 @@ impl Plant {
                 let new_branch_cost = Branch::calculate_cost_from_individual_parts(new_strength, new_photoreceptiveness, new_water_intake, new_length);
                 let cost = new_branch_cost - prev_branch_cost;
 
-                if self.current_energy - cost < self.genome.min_enegy_for_growth {
+                if self.current_energy - cost < self.genome.min_enegy_for_growth || self.current_nitrogen < cost * NITROGEN_PER_COST {
                     return false;
                 }
 
@@ -118,7 +175,46 @@ impl Plant {
                 self.branches[growth_priority_item.branch_index].length = new_length;
                 
 
+                //Shrinking a branch gives its nitrogen back to the plant
+                self.current_energy -= cost;
+                self.current_nitrogen -= cost * NITROGEN_PER_COST;
+            }
+            PriorityItemType::NewRoot(item) => {
+                if self.roots.len() >= PLANT_MAX_ROOT_COUNT {
+                    return true;
+                }
+
+                let cost = Root::calculate_cost_from_individual_parts(item.water_intake, item.length);
+
+                if self.current_energy - cost < self.genome.min_enegy_for_growth || self.current_nitrogen < cost * NITROGEN_PER_COST {
+                    return false;
+                }
+
+                //New roots grow off of any existing root or straight out of the base of the plant
+                let parent_index = rng.random_range(0 ..= self.roots.len());
+                let parent = if parent_index == self.roots.len() { None } else { Some(parent_index) };
+
+                self.roots.push(Root::new(parent, item.spread, item.water_intake, item.length, rng));
+
+                self.current_energy -= cost;
+                self.current_nitrogen -= cost * NITROGEN_PER_COST;
+            }
+            PriorityItemType::NewLeaf(item) => {
+                let branch = &self.branches[growth_priority_item.branch_index];
+                if branch.leaves.len() >= branch.max_leaf_count() {
+                    return true;
+                }
+
+                let cost = Leaf::calculate_cost_from_individual_parts(item.area);
+
+                if self.current_energy - cost < self.genome.min_enegy_for_growth || self.current_nitrogen < cost * NITROGEN_PER_COST {
+                    return false;
+                }
+
+                self.branches[growth_priority_item.branch_index].leaves.push(Leaf::new(item.area, item.angle, item.lifespan, rng));
+
                 self.current_energy -= cost;
+                self.current_nitrogen -= cost * NITROGEN_PER_COST;
             }
         }
 
@@ -136,12 +232,12 @@ impl Eq for GrowthPriorityItem {}
 
 impl PartialOrd for GrowthPriorityItem {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.priority.partial_cmp(&other.priority).map(|o| o.reverse())
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for GrowthPriorityItem {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.priority.partial_cmp(&other.priority).map(|o| o.reverse()).unwrap()
     }
 }
\ No newline at end of file
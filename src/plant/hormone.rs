@@ -0,0 +1,66 @@
+use rand::Rng;
+
+use crate::plant::Plant;
+use crate::plant::branch::Branch;
+
+// Signals that branches make through ProduceHormone rule outcomes and that rules can test for.
+// Each plant tick every branch's levels decay, auxin drains down from the tips towards the trunk and cytokinin spreads up from the trunk
+// towards the tips, so a branch can tell how much is growing above it or how well the parts below it are doing
+
+pub const HORMONE_COUNT: usize = 2;
+pub const HORMONE_DECAY: f32 = 0.1;             // Fraction of each branch's hormone lost every plant tick
+pub const HORMONE_TRANSPORT_RATE: f32 = 0.2;    // Fraction of a branch's hormone that moves on to the next branch every plant tick
+pub const HORMONE_MAX_LEVEL: f32 = 5.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Hormone {
+    Auxin,          // Flows from offshoots down to the branch they grow from
+    Cytokinin       // Flows from a branch up into its offshoots
+}
+
+impl Hormone {
+    pub const ALL: [Hormone; HORMONE_COUNT] = [
+        Hormone::Auxin,
+        Hormone::Cytokinin
+    ];
+
+    pub fn index(&self) -> usize {
+        return Hormone::ALL.iter().position(|other| other == self).unwrap();
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hormone::Auxin => "Auxin",
+            Hormone::Cytokinin => "Cytokinin"
+        }
+    }
+
+    pub fn random<R: Rng>(rng: &mut R) -> Hormone {
+        return Hormone::ALL[rng.random_range(0..HORMONE_COUNT)];
+    }
+}
+
+impl Branch {
+    pub fn hormone(&self, hormone: Hormone) -> f32 {
+        return self.hormones[hormone.index()];
+    }
+
+    pub fn produce_hormone(&mut self, hormone: Hormone, amount: f32) {
+        let level = &mut self.hormones[hormone.index()];
+        *level = (*level + amount).clamp(0.0, HORMONE_MAX_LEVEL);
+    }
+
+    pub fn decay_hormones(&mut self) {
+        for level in self.hormones.iter_mut() {
+            *level *= 1.0 - HORMONE_DECAY;
+        }
+    }
+}
+
+impl Plant {
+    pub fn move_hormone(&mut self, hormone: Hormone, from_branch_index: usize, to_branch_index: usize, amount: f32) {
+        let amount = amount.min(self.branches[from_branch_index].hormone(hormone));
+        self.branches[from_branch_index].hormones[hormone.index()] -= amount;
+        self.branches[to_branch_index].produce_hormone(hormone, amount);
+    }
+}
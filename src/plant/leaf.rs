@@ -0,0 +1,84 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::render::mat4_def::Mat4;
+use crate::world::WorldRng;
+use crate::world::world_clock::SunState;
+
+// Leaves are where a plant catches its sunlight. They grow off of branches, cost upkeep like everything else,
+// and are shed once they get old or when a rule asks for it, giving part of what they cost back to the plant.
+
+pub const BRANCH_MAX_LEAVES: usize = 4;         // Leaves a branch with full photoreceptiveness can hold
+pub const LEAF_RESORB_FRACTION: f32 = 0.5;      // Share of a shed leaf's cost the plant takes back before it drops
+const LEAF_SUNLIGHT_FACTOR: f32 = 1.5;          // Sunlight a unit of leaf area facing straight into full sun catches
+
+pub struct Leaf {
+    pub area: f32,
+    pub yaw: f32,               // Around the branch
+    pub pitch: f32,             // Away from the branch, PI / 2 sticks straight out from it
+    pub along_length: f32,
+    pub lifespan: f32,          // In plant ticks
+    pub age_ticks: u32
+}
+
+impl Leaf {
+    pub fn new(area: f32, angle: f32, lifespan: f32, rng: &mut WorldRng) -> Leaf {
+        return Leaf {
+            area: area * rng.random_range(0.9 .. 1.1),
+            yaw: rng.random_range(0.0 .. 2.0 * PI),
+            pitch: angle.clamp(0.0, PI) + rng.random_range(-0.1 .. 0.1),
+            along_length: rng.random_range(0.3 .. 1.0),
+            lifespan: lifespan * rng.random_range(0.9 .. 1.1),
+            age_ticks: 0
+        }
+    }
+
+    pub fn calculate_cost_from_individual_parts(area: f32) -> f32 {
+        return 2.0 + 6.0 * area.max(0.0);
+    }
+
+    pub fn calculate_cost(&self) -> f32 {
+        return Self::calculate_cost_from_individual_parts(self.area);
+    }
+
+    pub fn calculate_homeostasis(&self) -> f32 {
+        return 0.01 + 0.03 * self.area;
+    }
+
+    pub fn real_size(&self) -> f32 {
+        return 1.0 + 3.0 * self.area.max(0.0).sqrt();
+    }
+
+    pub fn is_shed(&self) -> bool {
+        return self.age_ticks as f32 > self.lifespan;
+    }
+
+    pub fn leaf_matrix(&self, branch_length_real: f32, branch_matrix: &Mat4) -> Mat4 {
+        //The leaf blade runs along its local y axis and faces along its local z axis
+        return Mat4::rotation_y(self.yaw) *
+               Mat4::rotation_x(self.pitch) *
+               Mat4::translation(0.0, self.along_length * branch_length_real, 0.0) *
+               branch_matrix.clone();
+    }
+
+    pub fn calculate_collect_sunlight(&self, matrix: &Mat4, root_height: f32, light: f32, sun: &SunState) -> f32 {
+        //matrix is the leaf's own matrix. Leaves facing the sun catch the most of it, either side works.
+        //Leaves held high above the ground (and higher up the mountains) are less shaded
+        let base = matrix.mul_vec3_as_slice([0.0, 0.0, 0.0]);
+        let facing = matrix.mul_vec3_as_slice([0.0, 0.0, 1.0]);
+        let midpoint = matrix.mul_vec3_as_slice([0.0, self.real_size() * 0.5, 0.0]);
+
+        let alignment_with_sun = ((facing[0] - base[0]) * sun.direction.0 + (facing[1] - base[1]) * sun.direction.1 + (facing[2] - base[2]) * sun.direction.2).abs().min(1.0);
+        let exposure = 0.2 + 0.8 * alignment_with_sun;
+
+        let height_above_ground = midpoint[1].max(0.0);
+        let altitude = (root_height + height_above_ground).max(0.0);
+
+        return LEAF_SUNLIGHT_FACTOR * self.area.max(0.0) * sun.intensity * light * exposure * (1.0 + height_above_ground / 20.0) * (1.0 + altitude / 200.0);
+    }
+
+    pub fn calculate_shade(&self) -> f32 {
+        return 0.04 * self.area.max(0.0);
+    }
+}
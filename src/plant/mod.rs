@@ -1,8 +1,19 @@
 mod plant;
 mod branch_execute_genome;
+mod branch_mechanics;
 pub mod plant_option_vec;
+pub mod plant_spatial_index;
 pub mod branch;
+pub mod root;
 pub mod genome;
+pub mod genome_mutation;
+pub mod genome_crossover;
+pub mod genome_text;
+pub mod genome_validation;
 pub mod growth_priority_item;
+pub mod seed;
+pub mod death;
+pub mod hormone;
+pub mod leaf;
 
 pub use plant::Plant;
\ No newline at end of file
@@ -6,66 +6,126 @@ use crate::glium::Surface;
 use crate::render::camera::CameraState;
 use crate::render::mat4_def::Mat4;
 use crate::terrain::{Terrain, TERRAIN_CELL_WIDTH};
+use crate::terrain::nutrients::NITROGEN_PER_COST;
 use crate::plant::growth_priority_item::GrowthPriorityItem;
 use crate::plant::genome::PlantGenome;
 use crate::plant::branch::{Branch, BranchConnection};
+use crate::plant::root::Root;
+use crate::plant::leaf::LEAF_RESORB_FRACTION;
+use crate::plant::seed::{Seed, MAX_SEEDS_PER_PLANT_TICK};
+use crate::plant::death::DeathCause;
+use crate::world::{WorldRng, LightGrid};
+use crate::world::light_grid::ShadeContribution;
+use crate::world::world_clock::SunState;
 use crate::render::Vertex;
-use crate::render::branch_model::PlantModelMode;
-use crate::plant::growth_priority_item::PriorityItemType::{NewOffshoot, ModifyBranch};
+use crate::render::branch_model::{PlantModelMode, PlantMesh};
 
 use std::collections::BinaryHeap;
 
+use rand::Rng;
+
 pub const PLANT_MAX_BRANCH_COUNT: usize = 100;
+pub const PLANT_MAX_ROOT_COUNT: usize = 30;
+pub const PLANT_MAX_NITROGEN: f32 = 5.0;        // Most nitrogen a plant keeps in store for growing with
+const NITROGEN_UPTAKE_RATE: f32 = 0.1;          // Fraction of a cell's nitrogen a plant can take up per tick, per unit of draw on that cell
+const PLANT_DROWNING_TICKS: u32 = 10;           // Plant ticks in a row the base of a plant can be under water before it drowns
 
 pub struct Plant {
+    pub id: u64,                        // Unique for the whole run, handed out by PlantOptionVec::add_plant
+    pub mother_id: Option<u64>,         // None for plants that were spawned in rather than grown from a seed
+    pub father_id: Option<u64>,         // None for self pollinated seeds
     pub genome: PlantGenome,
     pub age_ticks: u64,
+    pub seeds_produced: u32,
+    pub submerged_ticks: u32,
 
     pub root_position: (f32, f32, f32),
     pub branches: Vec<Branch>,
+    pub branch_kill_requests: Vec<usize>,
+    pub leaf_shed_requests: Vec<usize>,     // Branches whose leaves all get shed at the end of the tick
+    pub shade_contributions: Vec<ShadeContribution>,
+
+    pub roots: Vec<Root>,
+    pub root_kill_requested: bool,
     
     pub current_energy: f32,
     pub current_water: f32,
     pub current_sunlight: f32,
+    pub current_nitrogen: f32,
 
+    pub pending_mesh: Option<PlantMesh>,
     pub cached_model: Option<(glium::VertexBuffer<Vertex>, glium::IndexBuffer<u32>)>
 }
 
 impl Debug for Plant {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> { 
-        return write!(f, "Plant: (Branch count: {:?}, Energy: {}, Water: {}, Sun: {})", self.branches.len(), self.current_energy, self.current_water, self.current_sunlight);
+        return write!(f, "Plant {}: (Mother: {:?}, Father: {:?}, Branch count: {:?}, Leaf count: {}, Root count: {}, Seeds produced: {}, Energy: {}, Water: {}, Sun: {}, Nitrogen: {})", self.id, self.mother_id, self.father_id, self.branches.len(), self.branches.iter().map(|branch| branch.leaves.len()).sum::<usize>(), self.roots.len(), self.seeds_produced, self.current_energy, self.current_water, self.current_sunlight, self.current_nitrogen);
     }
 }
 
 impl Plant {
-    pub fn tick(&mut self, terrain: &Terrain, display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>, model_mode: PlantModelMode) -> bool {
-        //Returns false when the plant has died and should be removed
+    pub fn tick(&mut self, terrain: &mut Terrain, sun: &SunState, light_grid: &mut LightGrid, model_mode: PlantModelMode, seeds: &mut Vec<Seed>, rng: &mut WorldRng) -> Option<DeathCause> {
+        //Returns what the plant died of when it has died and should be removed
 
         self.age_ticks += 1;
+        if self.age_ticks as f32 > self.genome.lifespan {
+            self.die(terrain, light_grid);
+            return Some(DeathCause::OldAge);
+        }
+
+        if terrain.get_waterlog(self.root_position.0, self.root_position.2) > 0.0 {
+            self.submerged_ticks += 1;
+        } else {
+            self.submerged_ticks = 0;
+        }
+        if self.submerged_ticks > PLANT_DROWNING_TICKS {
+            self.die(terrain, light_grid);
+            return Some(DeathCause::Drowning);
+        }
 
         let mut homeostasis: f32 = 2.0;
         let mut growth_priority_heap: BinaryHeap<GrowthPriorityItem> = BinaryHeap::new();
 
         let matrix = Mat4::identity();
+        let root_cells = self.calculate_root_cells();
+        let root_water_shares = self.calculate_root_water_shares(terrain, &root_cells);
+        let water_availability: f32 = root_water_shares.iter().map(|(_x, _z, share)| share).sum();
+        let water_before = self.current_water;
 
         let mut vertices: Vec<Vertex> = vec![];
         let mut indices: Vec<u32> = vec![];
+        let mut shade_contributions: Vec<ShadeContribution> = vec![];
+
+        self.execute_branch_and_update_model_recursive(&mut homeostasis, 0, &mut growth_priority_heap, 0, terrain, sun, water_availability, light_grid, &mut shade_contributions, &mut vertices, &mut indices, matrix, model_mode, rng);
+
+        for root in &self.roots {
+            homeostasis += root.calculate_homeostasis();
+        }
+
+        //The water the branches took up has to come out of the soil the roots reach, which might not have had all of it
+        let water_collected = self.current_water - water_before;
+        let mut water_drawn: f32 = 0.0;
+        if water_availability > 0.0 {
+            for (x, z, share) in &root_water_shares {
+                water_drawn += terrain.draw_water(*x, *z, water_collected * share / water_availability);
+            }
+        }
+        self.current_water -= water_collected - water_drawn;
+
+        self.take_up_nitrogen(terrain, &root_cells);
 
-        self.execute_branch_and_update_model_recursive(&mut homeostasis, 0, &mut growth_priority_heap, 0, terrain, &mut vertices, &mut indices, matrix, model_mode);
+        //Swaps the shade this plant cast last time it ticked for the shade of its branches now
+        light_grid.remove(&self.shade_contributions);
+        light_grid.add(&shade_contributions);
+        self.shade_contributions = shade_contributions;
 
         match model_mode {
             PlantModelMode::NoModelUpdate => (),
             PlantModelMode::Ldm | PlantModelMode::Normal => {
-                self.cached_model = Some((
-                    glium::VertexBuffer::new(display, &vertices).unwrap(), 
-                    glium::IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices).unwrap()
-                ));
+                self.pending_mesh = Some(PlantMesh { vertices, indices, primitive_type: glium::index::PrimitiveType::TrianglesList });
             },
             PlantModelMode::SuperLdm => {
-                self.cached_model = Some((
-                    glium::VertexBuffer::new(display, &vertices).unwrap(), 
-                    glium::IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices).unwrap()
-                ));
+                self.pending_mesh = Some(PlantMesh { vertices, indices, primitive_type: glium::index::PrimitiveType::LinesList });
             }
         }
 
@@ -82,13 +142,14 @@ impl Plant {
         self.current_energy -= homeostasis;
 
         if self.current_energy < 0.0 {
-            return false;
+            self.die(terrain, light_grid);
+            return Some(DeathCause::Starvation);
         }
 
         while !growth_priority_heap.is_empty() && self.branches.len() < PLANT_MAX_BRANCH_COUNT && self.current_energy > self.genome.min_enegy_for_growth {
             let growth_priority_item: GrowthPriorityItem = growth_priority_heap.pop().unwrap();
 
-            if !self.execute_growth_priority_item(&growth_priority_item) {
+            if !self.execute_growth_priority_item(&growth_priority_item, rng) {
                 // Executes when the growth priority item didn't execute because the plant didn't have enough energy 
                 // (for example, the new offshoot would result in the plant instantly dying)
 
@@ -96,23 +157,257 @@ impl Plant {
             }
         }
 
-        return true;
+        self.shed_leaves(terrain);
+        self.apply_branch_kill_requests(terrain);
+        self.apply_root_kill_request(terrain);
+        if self.apply_structural_load(terrain) {
+            self.die(terrain, light_grid);
+            return Some(DeathCause::Collapse);
+        }
+        self.produce_seeds(seeds, rng);
+
+        return None;
+    }
+
+    pub fn die(&mut self, terrain: &mut Terrain, light_grid: &mut LightGrid) {
+        //Stops shading its neighbours and rots where it stood. The plant still needs removing afterwards
+        light_grid.remove(&self.shade_contributions);
+        self.shade_contributions.clear();
+        self.return_to_soil(terrain);
     }
 
-    pub fn delete_branch_recursive(&mut self, branch_index: usize) {
-        match &self.branches[branch_index].offshoot_1 {
-            Some(connection) => {
-                self.delete_branch_recursive(connection.branch_index);
+    fn produce_seeds(&mut self, seeds: &mut Vec<Seed>, rng: &mut WorldRng) {
+        // Seeds only get made from the energy left over after growth, so a plant has to stop growing (or run out of growth requests) before it reproduces
+        let mut seed_count: usize = 0;
+        while seed_count < MAX_SEEDS_PER_PLANT_TICK && self.current_energy - self.genome.baby_energy > self.genome.min_enegy_for_growth {
+            seeds.push(Seed::disperse(self, rng));
+            self.current_energy -= self.genome.baby_energy;
+            self.seeds_produced += 1;
+            seed_count += 1;
+        }
+    }
+
+    fn take_up_nitrogen(&mut self, terrain: &mut Terrain, root_cells: &[(f32, f32, f32)]) {
+        //Each cell the roots reach gives up a fraction of its nitrogen in proportion to how strongly the plant draws on it, until the store is full
+        for (x, z, weight) in root_cells {
+            let demand = PLANT_MAX_NITROGEN - self.current_nitrogen;
+            if demand <= 0.0 {
+                return;
             }
+
+            let available = terrain.get_grid_node(*x, *z).nitrogen * (NITROGEN_UPTAKE_RATE * weight).min(1.0);
+            self.current_nitrogen += terrain.draw_nitrogen(*x, *z, available.min(demand));
+        }
+    }
+
+    fn return_to_soil(&mut self, terrain: &mut Terrain) {
+        //Everything the plant was built from, and whatever it had in store, rots where it stood
+        let mut nitrogen = self.current_nitrogen;
+        for branch in &self.branches {
+            nitrogen += (branch.calculate_cost() + branch.calculate_leaf_cost()) * NITROGEN_PER_COST;
+        }
+        for root in &self.roots {
+            nitrogen += root.calculate_cost() * NITROGEN_PER_COST;
+        }
+
+        terrain.add_detritus(self.root_position.0, self.root_position.2, nitrogen);
+        self.current_nitrogen = 0.0;
+    }
+
+    fn shed_leaves(&mut self, terrain: &mut Terrain) {
+        //Every leaf ages a tick, and the ones past their lifespan drop along with every leaf on the branches a rule asked to shed.
+        //The plant draws some of what went into each leaf back out before letting it go, the rest rots where it falls
+        let requests = std::mem::take(&mut self.leaf_shed_requests);
+        let mut dropped_cost: f32 = 0.0;
+
+        for (branch_index, branch) in self.branches.iter_mut().enumerate() {
+            let shed_all = requests.contains(&branch_index);
+            branch.leaves.retain_mut(|leaf| {
+                leaf.age_ticks += 1;
+                if shed_all || leaf.is_shed() {
+                    dropped_cost += leaf.calculate_cost();
+                    return false;
+                }
+                return true;
+            });
+        }
+
+        self.current_energy += dropped_cost * LEAF_RESORB_FRACTION;
+        terrain.add_detritus(self.root_position.0, self.root_position.2, dropped_cost * NITROGEN_PER_COST);
+    }
+
+    fn apply_branch_kill_requests(&mut self, terrain: &mut Terrain) {
+        // Kills are only requested during the branch traversal and carried out here, since deleting branches shifts the indices
+        // that the traversal and the growth priority heap are still holding on to
+        if self.branch_kill_requests.is_empty() {
+            return;
+        }
+
+        //The plant takes back what it put into everything it prunes, not just the branches the kills were aimed at
+        let requests = std::mem::take(&mut self.branch_kill_requests);
+        let dropped_cost = self.delete_branches(&requests);
+        self.current_energy += dropped_cost;
+        terrain.add_detritus(self.root_position.0, self.root_position.2, dropped_cost * NITROGEN_PER_COST);
+    }
+
+    fn apply_root_kill_request(&mut self, terrain: &mut Terrain) {
+        // The newest root never has anything growing off of it, since roots only ever grow off of older ones
+        if !self.root_kill_requested {
+            return;
+        }
+        self.root_kill_requested = false;
+
+        match self.roots.pop() {
+            Some(root) => {
+                self.current_energy += root.calculate_cost();
+                terrain.add_detritus(self.root_position.0, self.root_position.2, root.calculate_cost() * NITROGEN_PER_COST);
+            },
             None => ()
         }
-        match &self.branches[branch_index].offshoot_2 {
-            Some(connection) => {
-                self.delete_branch_recursive(connection.branch_index);
+    }
+
+    pub fn break_in_wind(&mut self, wind: f32, terrain: &mut Terrain, rng: &mut WorldRng) {
+        //The branch pushed furthest past breaking point might snap, taking everything growing off of it down with it.
+        //The trunk always holds, and nothing broken off is refunded since it all falls to the ground
+        let mut weakest_index: usize = 0;
+        let mut weakest_overload: f32 = 0.0;
+        for (branch_index, branch) in self.branches.iter().enumerate().skip(1) {
+            let overload = branch.calculate_wind_overload(wind);
+            if overload > weakest_overload {
+                weakest_index = branch_index;
+                weakest_overload = overload;
+            }
+        }
+
+        if weakest_index == 0 || rng.random_range(0.0 .. 1.0) >= weakest_overload {
+            return;
+        }
+
+        let dropped_cost = self.delete_branch_recursive(weakest_index);
+        terrain.add_detritus(self.root_position.0, self.root_position.2, dropped_cost * NITROGEN_PER_COST);
+    }
+
+    pub fn calculate_root_tips(&self) -> Vec<(f32, f32, f32)> {
+        //Where each root ends, relative to the base of the plant in world units
+        let mut tips: Vec<(f32, f32, f32)> = Vec::with_capacity(self.roots.len());
+        for root in &self.roots {
+            let start = match root.parent {
+                Some(parent_index) => tips[parent_index],
+                None => (0.0, 0.0, 0.0)
+            };
+            let direction = root.direction();
+            tips.push((
+                start.0 + direction.0 * root.real_length(),
+                start.1 + direction.1 * root.real_length(),
+                start.2 + direction.2 * root.real_length()
+            ));
+        }
+        return tips;
+    }
+
+    pub fn calculate_root_cells(&self) -> Vec<(f32, f32, f32)> {
+        //Every spot (in terrain cells) the plant draws from the soil at, with how strongly it draws there.
+        //The base of the plant always draws with a weight of 1, each root adds its own draw on the cell its tip reaches
+        let mut cells: Vec<(f32, f32, f32)> = vec![(self.root_position.0, self.root_position.2, 1.0)];
+
+        for (root, tip) in self.roots.iter().zip(self.calculate_root_tips()) {
+            cells.push((
+                self.root_position.0 + tip.0 / TERRAIN_CELL_WIDTH,
+                self.root_position.2 + tip.2 / TERRAIN_CELL_WIDTH,
+                root.calculate_absorption(-tip.1)
+            ));
+        }
+        return cells;
+    }
+
+    fn calculate_root_water_shares(&self, terrain: &Terrain, root_cells: &[(f32, f32, f32)]) -> Vec<(f32, f32, f32)> {
+        let mut shares = root_cells.to_vec();
+        for (x, z, weight) in &mut shares {
+            *weight *= terrain.get_water_availability(*x, *z);
+        }
+        return shares;
+    }
+
+    pub fn delete_branch_recursive(&mut self, branch_index: usize) -> f32 {
+        return self.delete_branches(&[branch_index]);
+    }
+
+    pub fn delete_branches(&mut self, branch_indices: &[usize]) -> f32 {
+        //Removes every listed branch along with everything growing off of it, then packs the remaining branches back together and fixes up their connections.
+        //Returns the combined cost of everything that got removed
+        let mut doomed: Vec<bool> = vec![false; self.branches.len()];
+        for branch_index in branch_indices {
+            self.mark_branch_recursive(*branch_index, &mut doomed);
+        }
+
+        let mut removed_cost: f32 = 0.0;
+        for (branch, is_doomed) in self.branches.iter().zip(&doomed) {
+            if *is_doomed {
+                removed_cost += branch.calculate_cost() + branch.calculate_leaf_cost();
+            }
+        }
+
+        let mut remap: Vec<Option<usize>> = Vec::with_capacity(self.branches.len());
+        let mut next_index: usize = 0;
+        for is_doomed in &doomed {
+            if *is_doomed {
+                remap.push(None);
+            } else {
+                remap.push(Some(next_index));
+                next_index += 1;
+            }
+        }
+
+        let mut i: usize = 0;
+        self.branches.retain(|_| {
+            i += 1;
+            return !doomed[i - 1];
+        });
+
+        for branch in &mut self.branches {
+            branch.offshoots = std::mem::take(&mut branch.offshoots).into_iter().filter_map(|connection| remap[connection.branch_index].map(|new_index| BranchConnection { branch_index: new_index, ..connection })).collect();
+        }
+
+        return removed_cost;
+    }
+
+    fn mark_branch_recursive(&self, branch_index: usize, doomed: &mut Vec<bool>) {
+        doomed[branch_index] = true;
+        for connection in &self.branches[branch_index].offshoots {
+            self.mark_branch_recursive(connection.branch_index, doomed);
+        }
+    }
+
+    pub fn calculate_subtree_cost(&self, branch_index: usize) -> f32 {
+        //Cost of the branch, its leaves and everything growing off of it
+        let branch = &self.branches[branch_index];
+        let mut cost = branch.calculate_cost() + branch.calculate_leaf_cost();
+        for connection in &branch.offshoots {
+            cost += self.calculate_subtree_cost(connection.branch_index);
+        }
+        return cost;
+    }
+
+    pub fn calculate_subtree_sunlight(&self, branch_index: usize) -> f32 {
+        //Sunlight the branch and everything growing off of it caught the last time the plant ticked
+        let branch = &self.branches[branch_index];
+        let mut sunlight = branch.collected_sunlight;
+        for connection in &branch.offshoots {
+            sunlight += self.calculate_subtree_sunlight(connection.branch_index);
+        }
+        return sunlight;
+    }
+
+    pub fn upload_model(&mut self, display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>) {
+        match self.pending_mesh.take() {
+            Some(mesh) => {
+                self.cached_model = Some((
+                    glium::VertexBuffer::new(display, &mesh.vertices).unwrap(), 
+                    glium::IndexBuffer::new(display, mesh.primitive_type, &mesh.indices).unwrap()
+                ));
             }
             None => ()
         }
-        self.branches.remove(branch_index);
     }
 
     pub fn render(
@@ -120,7 +415,7 @@ impl Plant {
         total_time: f32,
         target: &mut glium::Frame, 
         program: &glium::Program, 
-        display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>,
+        _display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>,
         camera: &CameraState,
         params: &DrawParameters
     ) {
@@ -142,17 +437,32 @@ impl Plant {
         
     }
 
-    pub fn new (genome: PlantGenome, x: f32, z: f32, starting_energy: f32, terrain: &Terrain) -> Plant {
+    pub fn new (genome: PlantGenome, x: f32, z: f32, starting_energy: f32, terrain: &Terrain, rng: &mut WorldRng) -> Plant {
         return Plant {
-            branches: vec![Branch::new(genome.sapling_strength, genome.sapling_photoreceptiveness, genome.sapling_water_intake, genome.sapling_length)],
+            branches: vec![Branch::new(genome.sapling_strength, genome.sapling_photoreceptiveness, genome.sapling_water_intake, genome.sapling_length, rng)],
+            branch_kill_requests: vec![],
+            leaf_shed_requests: vec![],
+            shade_contributions: vec![],
+
+            roots: vec![],
+            root_kill_requested: false,
+
+            id: 0,
+            mother_id: None,
+            father_id: None,
+
             age_ticks: 0,
+            seeds_produced: 0,
+            submerged_ticks: 0,
 
-            genome: genome,
+            genome,
             root_position: (x, terrain.get_height(x, z), z),
             current_energy: starting_energy,
             current_sunlight: 0.0,
             current_water: 0.0,
+            current_nitrogen: 0.0,
 
+            pending_mesh: None,
             cached_model: None
         }
     }
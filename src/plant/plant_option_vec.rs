@@ -1,17 +1,44 @@
+use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter};
 
 use crate::plant::Plant;
+use crate::plant::seed::{Seed, SEED_MIN_SPACING};
+use crate::plant::death::{DeathCause, DeathRecord, MAX_DEATH_RECORDS};
+use crate::plant::genome::PlantGenome;
+use crate::plant::genome_mutation::MutationRates;
+use crate::plant::genome_crossover::CrossoverStrategy;
+use crate::plant::plant_spatial_index::PlantSpatialIndex;
 use crate::render::branch_model::PlantModelMode;
 use crate::terrain::{Terrain, TERRAIN_CELL_WIDTH};
 
 use crate::render::vector_math;
+use crate::world::{WorldRng, LightGrid};
+use crate::world::world_clock::SunState;
 
-use glium::{DrawParameters};
+use rand::Rng;
+
+use glium::DrawParameters;
 use crate::render::camera::CameraState;
 
+// Slot indices get reused once a plant dies, so anything that needs to keep referring to one plant should hold a PlantHandle instead.
+// Every slot counts how many plants have died in it, and a handle only works while that count matches the one it was made with
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlantHandle {
+    pub index: usize,
+    pub generation: u32
+}
+
 pub struct PlantOptionVec {
     pub internal_vec: Vec<Option<Box<Plant>>>,
-    first_none: Option<usize>
+    pub generations: Vec<u32>,          // One per slot in internal_vec
+    pub next_plant_id: u64,
+    first_none: Option<usize>,
+    pub spatial_index: PlantSpatialIndex,
+
+    pub seeds: Vec<Seed>,
+    pub death_records: VecDeque<DeathRecord>,
+    pub mutation_rates: MutationRates,
+    pub crossover_strategy: CrossoverStrategy
 }
 
 impl Debug for PlantOptionVec {
@@ -22,7 +49,19 @@ impl Debug for PlantOptionVec {
 
 impl PlantOptionVec {
     pub fn new() -> PlantOptionVec {
-        return PlantOptionVec { internal_vec: vec![], first_none: None }
+        return PlantOptionVec { internal_vec: vec![], generations: vec![], next_plant_id: 0, first_none: None, spatial_index: PlantSpatialIndex::empty(), seeds: vec![], death_records: VecDeque::new(), mutation_rates: MutationRates::new(), crossover_strategy: CrossoverStrategy::RuleAligned }
+    }
+
+    pub fn from_slots(internal_vec: Vec<Option<Box<Plant>>>, generations: Vec<u32>, next_plant_id: u64, seeds: Vec<Seed>, death_records: VecDeque<DeathRecord>) -> PlantOptionVec {
+        let first_none = internal_vec.iter().position(|item| item.is_none());
+        let mut spatial_index = PlantSpatialIndex::empty();
+        for (i, item) in internal_vec.iter().enumerate() {
+            match item {
+                Some(plant) => spatial_index.insert(i, plant.root_position.0, plant.root_position.2),
+                None => ()
+            }
+        }
+        return PlantOptionVec { internal_vec, generations, next_plant_id, first_none, spatial_index, seeds, death_records, mutation_rates: MutationRates::new(), crossover_strategy: CrossoverStrategy::RuleAligned }
     }
 
     fn move_first_move_up(&mut self) {
@@ -41,66 +80,231 @@ impl PlantOptionVec {
         }
     }
     
-    pub fn add_plant(&mut self, plant: Plant) {
+    pub fn add_plant(&mut self, mut plant: Plant) -> PlantHandle {
+        //Gives the plant its ID and puts it in the first free slot
+        plant.id = self.next_plant_id;
+        self.next_plant_id += 1;
+
+        let (x, z) = (plant.root_position.0, plant.root_position.2);
+        let index: usize;
         match self.first_none {
-            Some(index) => {
+            Some(free_index) => {
+                index = free_index;
                 self.internal_vec[index] = Some(Box::new(plant));
                 self.move_first_move_up();
             },
             None => {
+                index = self.internal_vec.len();
                 self.internal_vec.push(Some(Box::new(plant)));
+                self.generations.push(0);
             }
         };
+        self.spatial_index.insert(index, x, z);
+
+        return PlantHandle { index, generation: self.generations[index] };
+    }
+
+    pub fn handle(&self, index: usize) -> Option<PlantHandle> {
+        //Handle to whatever plant is in the slot right now
+        match self.internal_vec.get(index) {
+            Some(Some(_)) => Some(PlantHandle { index, generation: self.generations[index] }),
+            _ => None
+        }
+    }
+
+    pub fn get(&self, handle: PlantHandle) -> Option<&Plant> {
+        //None once the plant the handle was made for has died, even if another plant has taken its slot since
+        if self.generations.get(handle.index) != Some(&handle.generation) {
+            return None;
+        }
+        return self.internal_vec[handle.index].as_deref();
+    }
+
+    pub fn get_mut(&mut self, handle: PlantHandle) -> Option<&mut Plant> {
+        if self.generations.get(handle.index) != Some(&handle.generation) {
+            return None;
+        }
+        return self.internal_vec[handle.index].as_deref_mut();
+    }
+
+    pub fn find_by_id(&self, id: u64) -> Option<PlantHandle> {
+        for (index, item) in self.internal_vec.iter().enumerate() {
+            match item {
+                Some(plant) => {
+                    if plant.id == id {
+                        return self.handle(index);
+                    }
+                },
+                None => ()
+            }
+        }
+        return None;
+    }
+
+    pub fn remove_plant(&mut self, index: usize, cause: DeathCause, total_ticks: u64) {
+        //Frees up the plant's slot and keeps a record of what it died of. The plant should have already died with Plant::die
+        match self.internal_vec[index].take() {
+            Some(plant) => {
+                self.generations[index] += 1;
+                self.spatial_index.remove(index, plant.root_position.0, plant.root_position.2);
+                self.death_records.push_back(DeathRecord::new(&plant, cause, total_ticks));
+                if self.death_records.len() > MAX_DEATH_RECORDS {
+                    self.death_records.pop_front();
+                }
+            },
+            None => {
+                return;
+            }
+        }
+
+        match self.first_none {
+            Some(first_none) => {
+                if index < first_none {
+                    self.first_none = Some(index);
+                }
+            },
+            None => {
+                self.first_none = Some(index);
+            }
+        }
     }
 
-    pub fn tick(&mut self, terrain: &Terrain, total_ticks: u64, display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>, camera: &CameraState) {
+    pub fn death_counts(&self) -> Vec<(DeathCause, usize)> {
+        //How many of the recorded deaths came down to each cause
+        return DeathCause::ALL.iter().map(|cause| (*cause, self.death_records.iter().filter(|record| record.cause == *cause).count())).collect();
+    }
+
+    pub fn tick(&mut self, terrain: &mut Terrain, sun: &SunState, light_grid: &mut LightGrid, total_ticks: u64, viewer_position: Option<(f32, f32, f32)>, rng: &mut WorldRng) {
+        //viewer_position is None when running headless, in which case no plant models get built at all
         const PLANT_TICK_MOD: u64 = 30;
 
+        let mut new_seeds: Vec<Seed> = vec![];
+        let mut new_seed_mothers: Vec<usize> = vec![];
+
         let mut i = total_ticks.rem_euclid(PLANT_TICK_MOD);
         while i < self.internal_vec.len() as u64 {
             let item = &mut self.internal_vec[i as usize];
             
             match item {
                 Some(plant) => {
-                    let dist_to_camera = vector_math::len_xz(vector_math::difference(plant.root_position, vector_math::scalar_multiple(1.0 / TERRAIN_CELL_WIDTH, camera.position)));
-
                     let model_mode: PlantModelMode;
 
-                    if dist_to_camera > 35.0 {
-                        if (total_ticks / (PLANT_TICK_MOD + 1)) & 8 == i & 8 {
-                            model_mode = PlantModelMode::SuperLdm;
-                        } else  {
+                    match viewer_position {
+                        None => {
                             model_mode = PlantModelMode::NoModelUpdate;
                         }
-                    } else if dist_to_camera > 15.0 {
-                        model_mode = PlantModelMode::Ldm;
-                    } else {
-                        model_mode = PlantModelMode::Normal;
-                    }
-
-                    if !plant.tick(terrain, display, model_mode) {
-                        //Delete from internal vector if it returns false
-                        *item = None;
+                        Some(viewer_position) => {
+                            let dist_to_camera = vector_math::len_xz(vector_math::difference(plant.root_position, vector_math::scalar_multiple(1.0 / TERRAIN_CELL_WIDTH, viewer_position)));
 
-                        match self.first_none {
-                            Some(index) => {
-                                if i < index as u64 {
-                                    self.first_none = Some(i as usize)
+                            if dist_to_camera > 35.0 {
+                                if (total_ticks / (PLANT_TICK_MOD + 1)) & 8 == i & 8 {
+                                    model_mode = PlantModelMode::SuperLdm;
+                                } else  {
+                                    model_mode = PlantModelMode::NoModelUpdate;
                                 }
-                            },
-                            None => {
-                                self.first_none = Some(i as usize);
+                            } else if dist_to_camera > 15.0 {
+                                model_mode = PlantModelMode::Ldm;
+                            } else {
+                                model_mode = PlantModelMode::Normal;
                             }
                         }
+                    }
 
-                        continue;
+                    match plant.tick(terrain, sun, light_grid, model_mode, &mut new_seeds, rng) {
+                        Some(cause) => {
+                            //Delete from internal vector once it has died
+                            self.remove_plant(i as usize, cause, total_ticks);
+                            i += PLANT_TICK_MOD;
+                            continue;
+                        },
+                        None => ()
                     }
+
+                    // Every seed this plant just made gets tagged with its index so it can be pollinated below
+                    new_seed_mothers.resize(new_seeds.len(), i as usize);
                 },
                 None => ()
             }
 
             i += PLANT_TICK_MOD;
         }
+
+        self.pollinate(&mut new_seeds, &new_seed_mothers, rng);
+        self.seeds.append(&mut new_seeds);
+        self.tick_seeds(terrain, rng);
+    }
+
+    fn tick_seeds(&mut self, terrain: &Terrain, rng: &mut WorldRng) {
+        let mut germinating: Vec<Seed> = vec![];
+        let mut i = 0;
+        while i < self.seeds.len() {
+            if self.seeds[i].ticks_until_germination == 0 {
+                germinating.push(self.seeds.swap_remove(i));
+                continue;
+            }
+            self.seeds[i].ticks_until_germination -= 1;
+            i += 1;
+        }
+
+        for mut seed in germinating {
+            if !seed.can_germinate(terrain) || self.is_crowded(seed.x, seed.z, SEED_MIN_SPACING) {
+                continue;
+            }
+            seed.genome.mutate(&self.mutation_rates, rng);
+            self.add_plant(seed.germinate(terrain, rng));
+        }
+    }
+
+    fn pollinate(&self, seeds: &mut [Seed], mothers: &[usize], rng: &mut WorldRng) {
+        //Seeds whose mother has another plant within her pollination range become a cross of the two, the rest stay clones of the mother
+        for (seed, mother_index) in seeds.iter_mut().zip(mothers) {
+            let mother = match &self.internal_vec[*mother_index] {
+                Some(plant) => plant,
+                None => continue
+            };
+
+            match self.find_mate(*mother_index, mother.genome.pollination_range, rng) {
+                Some(father_index) => {
+                    let father = self.internal_vec[father_index].as_ref().unwrap();
+                    seed.genome = PlantGenome::crossover(&mother.genome, &father.genome, self.crossover_strategy, rng);
+                    seed.father_id = Some(father.id);
+                },
+                None => ()
+            }
+        }
+    }
+
+    pub fn find_mate(&self, plant_index: usize, range: f32, rng: &mut WorldRng) -> Option<usize> {
+        //Picks a random living plant within range of the given plant, not counting itself
+        let position = match &self.internal_vec[plant_index] {
+            Some(plant) => plant.root_position,
+            None => return None
+        };
+
+        let candidates: Vec<usize> = self.spatial_index.query_radius(position.0, position.2, range).iter()
+            .map(|(i, _)| *i)
+            .filter(|i| *i != plant_index)
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+        return Some(candidates[rng.random_range(0..candidates.len())]);
+    }
+
+    pub fn is_crowded(&self, x: f32, z: f32, radius: f32) -> bool {
+        return self.spatial_index.any_within(x, z, radius);
+    }
+
+    pub fn upload_models(&mut self, display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>) {
+        for item in &mut self.internal_vec {
+            match item {
+                Some(plant) => {
+                    plant.upload_model(display);
+                }
+                None => ()
+            }
+        }
     }
 
     pub fn render(
@@ -0,0 +1,230 @@
+use crate::render::vector_math;
+use crate::terrain::TERRAIN_GRID_ROWS;
+
+// Uniform grid over the terrain cells that buckets living plants by where their root is, so neighbourhood queries only look at nearby cells
+// instead of every plant. Wraps around the world edges like the terrain does. Plants never move, so entries only change when plants are added or die.
+// Query results are always sorted by plant index, so they come out the same no matter what order the plants went in
+
+#[derive(Clone, Copy)]
+struct SpatialEntry {
+    plant_index: usize,
+    x: f32,
+    z: f32
+}
+
+pub struct PlantSpatialIndex {
+    cells: Vec<Vec<SpatialEntry>>
+}
+
+impl PlantSpatialIndex {
+    pub fn empty() -> PlantSpatialIndex {
+        return PlantSpatialIndex { cells: vec![vec![]; TERRAIN_GRID_ROWS * TERRAIN_GRID_ROWS] };
+    }
+
+    fn cell_coord(val: f32) -> usize {
+        //val is in terrain cells, like plant root positions
+        return (val.rem_euclid(TERRAIN_GRID_ROWS as f32).floor() as usize).min(TERRAIN_GRID_ROWS - 1);
+    }
+
+    fn cell_index(x_cell: usize, z_cell: usize) -> usize {
+        return x_cell * TERRAIN_GRID_ROWS + z_cell;
+    }
+
+    fn wrapped_dist(x_1: f32, z_1: f32, x_2: f32, z_2: f32) -> f32 {
+        return vector_math::dist_xz_wrapped((x_1, 0.0, z_1), (x_2, 0.0, z_2), TERRAIN_GRID_ROWS as f32);
+    }
+
+    pub fn insert(&mut self, plant_index: usize, x: f32, z: f32) {
+        let cell = Self::cell_index(Self::cell_coord(x), Self::cell_coord(z));
+        self.cells[cell].push(SpatialEntry { plant_index, x, z });
+    }
+
+    pub fn remove(&mut self, plant_index: usize, x: f32, z: f32) {
+        let cell = Self::cell_index(Self::cell_coord(x), Self::cell_coord(z));
+        match self.cells[cell].iter().position(|entry| entry.plant_index == plant_index) {
+            Some(position) => {
+                self.cells[cell].swap_remove(position);
+            },
+            None => ()
+        }
+    }
+
+    fn cells_in_range(centre: f32, radius: f32) -> Vec<usize> {
+        //Every cell coordinate within radius of centre along one axis, each only once even when the range wraps all the way around
+        let low = (centre - radius).floor() as i64;
+        let high = (centre + radius).floor() as i64;
+        if high - low + 1 >= TERRAIN_GRID_ROWS as i64 {
+            return (0..TERRAIN_GRID_ROWS).collect();
+        }
+        return (low..=high).map(|coord| coord.rem_euclid(TERRAIN_GRID_ROWS as i64) as usize).collect();
+    }
+
+    pub fn query_radius(&self, x: f32, z: f32, radius: f32) -> Vec<(usize, f32)> {
+        //Every plant within radius of (x, z) along with its distance, sorted by plant index
+        let mut found: Vec<(usize, f32)> = vec![];
+        if radius < 0.0 {
+            return found;
+        }
+
+        let z_cells = Self::cells_in_range(z, radius);
+        for x_cell in Self::cells_in_range(x, radius) {
+            for z_cell in &z_cells {
+                for entry in &self.cells[Self::cell_index(x_cell, *z_cell)] {
+                    let dist = Self::wrapped_dist(entry.x, entry.z, x, z);
+                    if dist <= radius {
+                        found.push((entry.plant_index, dist));
+                    }
+                }
+            }
+        }
+
+        found.sort_by_key(|(plant_index, _)| *plant_index);
+        return found;
+    }
+
+    pub fn any_within(&self, x: f32, z: f32, radius: f32) -> bool {
+        //Cheaper than query_radius when all that matters is whether there's anything there. Strictly closer than radius
+        let z_cells = Self::cells_in_range(z, radius);
+        for x_cell in Self::cells_in_range(x, radius) {
+            for z_cell in &z_cells {
+                for entry in &self.cells[Self::cell_index(x_cell, *z_cell)] {
+                    if Self::wrapped_dist(entry.x, entry.z, x, z) < radius {
+                        return true;
+                    }
+                }
+            }
+        }
+        return false;
+    }
+
+    pub fn k_nearest(&self, x: f32, z: f32, k: usize, max_radius: f32) -> Vec<(usize, f32)> {
+        //Up to k plants closest to (x, z) and no further than max_radius, nearest first. Ties go to the lower plant index.
+        //Searches outwards one ring of cells at a time, stopping once no unsearched cell could hold anything closer than what's been found
+        let mut found: Vec<(usize, f32)> = vec![];
+        if k == 0 {
+            return found;
+        }
+
+        let centre_x = Self::cell_coord(x) as i64;
+        let centre_z = Self::cell_coord(z) as i64;
+        let max_ring = (TERRAIN_GRID_ROWS / 2) as i64;
+
+        for ring in 0..=max_ring {
+            for x_offset in -ring..=ring {
+                for z_offset in -ring..=ring {
+                    if x_offset.abs() != ring && z_offset.abs() != ring {
+                        continue;
+                    }
+                    let x_cell = (centre_x + x_offset).rem_euclid(TERRAIN_GRID_ROWS as i64) as usize;
+                    let z_cell = (centre_z + z_offset).rem_euclid(TERRAIN_GRID_ROWS as i64) as usize;
+
+                    for entry in &self.cells[Self::cell_index(x_cell, z_cell)] {
+                        let dist = Self::wrapped_dist(entry.x, entry.z, x, z);
+                        if dist <= max_radius {
+                            found.push((entry.plant_index, dist));
+                        }
+                    }
+                }
+            }
+
+            //The last ring can wrap back around onto cells that were already searched
+            found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+            found.dedup_by_key(|(plant_index, _)| *plant_index);
+            found.truncate(k);
+
+            //Anything in a ring further out is at least this far away
+            let unsearched_dist = ring as f32;
+            if unsearched_dist > max_radius || (found.len() == k && found[k - 1].1 <= unsearched_dist) {
+                break;
+            }
+        }
+
+        return found;
+    }
+
+    pub fn nearest(&self, x: f32, z: f32, max_radius: f32) -> Option<(usize, f32)> {
+        return self.k_nearest(x, z, 1, max_radius).first().copied();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDGE: f32 = TERRAIN_GRID_ROWS as f32;
+
+    fn index_of(positions: &[(f32, f32)]) -> PlantSpatialIndex {
+        let mut index = PlantSpatialIndex::empty();
+        for (plant_index, (x, z)) in positions.iter().enumerate() {
+            index.insert(plant_index, *x, *z);
+        }
+        return index;
+    }
+
+    fn brute_force_k_nearest(positions: &[(f32, f32)], x: f32, z: f32, k: usize, max_radius: f32) -> Vec<(usize, f32)> {
+        let mut found: Vec<(usize, f32)> = positions.iter().enumerate()
+            .map(|(plant_index, (plant_x, plant_z))| (plant_index, PlantSpatialIndex::wrapped_dist(*plant_x, *plant_z, x, z)))
+            .filter(|(_, dist)| *dist <= max_radius)
+            .collect();
+        found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        found.truncate(k);
+        return found;
+    }
+
+    #[test]
+    fn k_nearest_wraps_around_the_world_edge() {
+        let positions = [(EDGE - 0.5, 0.5), (0.5, EDGE - 0.5), (EDGE / 2.0, EDGE / 2.0), (0.5, 0.5), (3.0, EDGE - 3.0)];
+        let index = index_of(&positions);
+
+        //Plants 0 and 1 are the same distance away across opposite edges, so the lower index comes first
+        let found = index.k_nearest(0.2, 0.2, 3, 10.0);
+        let plant_indices: Vec<usize> = found.iter().map(|(plant_index, _)| *plant_index).collect();
+        assert!(plant_indices == vec![3, 0, 1], "{:?}", found);
+        assert!(found == brute_force_k_nearest(&positions, 0.2, 0.2, 3, 10.0));
+
+        //From the far corner everything but the middle plant is only a wrap away
+        let found = index.k_nearest(EDGE - 0.1, EDGE - 0.1, 5, 10.0);
+        assert!(found == brute_force_k_nearest(&positions, EDGE - 0.1, EDGE - 0.1, 5, 10.0), "{:?}", found);
+        assert!(found.len() == 4);
+    }
+
+    #[test]
+    fn k_nearest_with_more_asked_for_than_there_are_plants() {
+        let positions = [(10.0, 10.0), (EDGE - 1.0, 40.0), (70.5, 0.25), (62.5, 11.5)];
+        let index = index_of(&positions);
+
+        //A radius past half the world searches every ring, including the last one, which wraps back onto cells it already searched.
+        //Plant 3 sits in one of those cells, half the world away from the query
+        let found = index.k_nearest(12.0, 11.0, 10, EDGE);
+        assert!(found.len() == positions.len(), "{:?}", found);
+        assert!(found == brute_force_k_nearest(&positions, 12.0, 11.0, 10, EDGE));
+
+        assert!(index.k_nearest(12.0, 11.0, 10, 1.0).is_empty());
+        assert!(PlantSpatialIndex::empty().k_nearest(12.0, 11.0, 10, EDGE).is_empty());
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        //Fixed pseudo random scatter, bunched up near the edges so plenty of distances wrap
+        let mut state: u32 = 12345;
+        let mut next = || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            return (state >> 8) as f32 / (1 << 24) as f32;
+        };
+        let mut positions: Vec<(f32, f32)> = vec![];
+        for _ in 0..200 {
+            let x = (next() * 20.0 - 10.0).rem_euclid(EDGE);
+            let z = next() * EDGE;
+            positions.push((x, z));
+        }
+        let index = index_of(&positions);
+
+        for _ in 0..100 {
+            let x = (next() * 30.0 - 15.0).rem_euclid(EDGE);
+            let z = next() * EDGE;
+            for (k, max_radius) in [(1, 5.0), (4, 15.0), (25, 60.0), (300, EDGE)] {
+                assert!(index.k_nearest(x, z, k, max_radius) == brute_force_k_nearest(&positions, x, z, k, max_radius));
+            }
+        }
+    }
+}
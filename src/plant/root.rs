@@ -0,0 +1,55 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::world::WorldRng;
+
+pub const ROOT_ABSORPTION_FACTOR: f32 = 1.5;    // How much a root adds to the plant's draw on the cell it reaches, relative to the base of the plant
+
+pub struct Root {
+    pub parent: Option<usize>,  // None grows straight out of the base of the plant
+    pub yaw: f32,
+    pub pitch: f32,             // Angle below horizontal, PI / 2 is straight down
+
+    pub water_intake: f32,
+    pub length: f32
+}
+
+impl Root {
+    pub fn new(parent: Option<usize>, spread: f32, water_intake: f32, length: f32, rng: &mut WorldRng) -> Root {
+        //spread 0 grows straight down, spread 1 grows out sideways just under the surface
+        return Root {
+            parent,
+            yaw: rng.random_range(0.0 .. 2.0 * PI),
+            pitch: (1.0 - spread.clamp(0.0, 1.0)) * (PI / 2.0) * rng.random_range(0.8 .. 1.0) + 0.05,
+
+            water_intake: water_intake + rng.random_range(-0.03 .. 0.03),
+            length: length * rng.random_range(0.9 .. 1.1)
+        }
+    }
+
+    pub fn real_length(&self) -> f32 {
+        return 1.0 + self.length * 10.0;
+    }
+
+    pub fn calculate_cost_from_individual_parts(water_intake: f32, length: f32) -> f32 {
+        return 3.0 + 4.0 * length * (water_intake + 0.5).powi(2);
+    }
+
+    pub fn calculate_cost(&self) -> f32 {
+        return Self::calculate_cost_from_individual_parts(self.water_intake, self.length);
+    }
+
+    pub fn calculate_homeostasis(&self) -> f32 {
+        return 0.01 + 0.03 * self.length * self.water_intake.powi(2);
+    }
+
+    pub fn calculate_absorption(&self, depth: f32) -> f32 {
+        //Deeper soil stays wetter for longer, so deep roots get a bit more out of the same cell
+        return ROOT_ABSORPTION_FACTOR * self.length.max(0.0) * self.water_intake.max(0.0) * (1.0 + depth.max(0.0) / 20.0);
+    }
+
+    pub fn direction(&self) -> (f32, f32, f32) {
+        return (self.pitch.cos() * self.yaw.cos(), -self.pitch.sin(), self.pitch.cos() * self.yaw.sin());
+    }
+}
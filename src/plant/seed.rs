@@ -0,0 +1,54 @@
+use std::f32::consts::PI;
+
+use crate::plant::Plant;
+use crate::plant::genome::PlantGenome;
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+use crate::world::WorldRng;
+
+use rand::Rng;
+
+pub const SEED_DISPERSAL_RADIUS: f32 = 4.0;     // In terrain cells
+pub const SEED_GERMINATION_TICKS: u64 = 60;     // In world ticks
+pub const SEED_MIN_SPACING: f32 = 0.75;         // Seeds landing closer than this to an existing plant don't germinate
+pub const MAX_SEEDS_PER_PLANT_TICK: usize = 4;
+
+pub struct Seed {
+    pub genome: PlantGenome,
+    pub x: f32,
+    pub z: f32,
+    pub energy: f32,
+    pub ticks_until_germination: u64,
+
+    pub mother_id: u64,
+    pub father_id: Option<u64>          // Filled in if the seed gets pollinated
+}
+
+impl Seed {
+    pub fn disperse(parent: &Plant, rng: &mut WorldRng) -> Seed {
+        let angle = rng.random_range(0.0 .. 2.0 * PI);
+        let dist = SEED_DISPERSAL_RADIUS * rng.random_range(0.0 .. 1.0_f32).sqrt();
+
+        return Seed {
+            genome: parent.genome.clone(),
+            x: (parent.root_position.0 + angle.cos() * dist).rem_euclid(TERRAIN_GRID_ROWS as f32),
+            z: (parent.root_position.2 + angle.sin() * dist).rem_euclid(TERRAIN_GRID_ROWS as f32),
+            energy: parent.genome.baby_energy,
+            ticks_until_germination: SEED_GERMINATION_TICKS,
+
+            mother_id: parent.id,
+            father_id: None
+        }
+    }
+
+    pub fn can_germinate(&self, terrain: &Terrain) -> bool {
+        //Seeds that land in the water rot
+        return terrain.get_height(self.x, self.z) >= terrain.water_height;
+    }
+
+    pub fn germinate(self, terrain: &Terrain, rng: &mut WorldRng) -> Plant {
+        let mut plant = Plant::new(self.genome, self.x, self.z, self.energy, terrain, rng);
+        plant.mother_id = Some(self.mother_id);
+        plant.father_id = self.father_id;
+        return plant;
+    }
+}
@@ -3,6 +3,13 @@ use crate::plant::Plant;
 use crate::render::Vertex;
 use crate::render::mat4_def::Mat4;
 
+// CPU-side copy of a plant's model, built during the simulation tick and only turned into GPU buffers when there's a display to upload to
+pub struct PlantMesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub primitive_type: glium::index::PrimitiveType
+}
+
 #[derive(Copy, Clone)]
 pub enum PlantModelMode {
     NoModelUpdate,
@@ -21,14 +28,16 @@ impl Plant {
         model_mode: PlantModelMode
     ) {
         let color: [f32; 4] = [self.branches[branch_index].strength, self.branches[branch_index].photoreceptiveness, self.branches[branch_index].water_intake, 1.0];
-        let branch_length_real = 1.0 + self.branches[branch_index].length * 10.0;
+        let branch_length_real = self.branches[branch_index].real_length();
 
         match model_mode {
             PlantModelMode::Normal => {
-                branch_model(color, Mat4::scale(4.0, branch_length_real, 4.0) * matrix, plant_vertices, plant_indices);
+                branch_model(color, Mat4::scale(4.0, branch_length_real, 4.0) * matrix.clone(), plant_vertices, plant_indices);
+                self.push_leaf_models(branch_index, plant_vertices, plant_indices, &matrix);
             },
             PlantModelMode::Ldm => {
-                branch_model_ldm(color, Mat4::scale(4.0, branch_length_real, 4.0) * matrix, plant_vertices, plant_indices);
+                branch_model_ldm(color, Mat4::scale(4.0, branch_length_real, 4.0) * matrix.clone(), plant_vertices, plant_indices);
+                self.push_leaf_models(branch_index, plant_vertices, plant_indices, &matrix);
             },
             PlantModelMode::SuperLdm => {
                 branch_model_simple_line(color, Mat4::scale(4.0, branch_length_real, 4.0) * matrix, plant_vertices, plant_indices);
@@ -38,6 +47,43 @@ impl Plant {
             }
         }
     }
+
+    fn push_leaf_models(&self, branch_index: usize, plant_vertices: &mut Vec<Vertex>, plant_indices: &mut Vec<u32>, branch_matrix: &Mat4) {
+        //Leaves go from green to yellow as they get older
+        let branch = &self.branches[branch_index];
+        for leaf in &branch.leaves {
+            let age_fraction = (leaf.age_ticks as f32 / leaf.lifespan.max(1.0)).clamp(0.0, 1.0);
+            let color: [f32; 4] = [0.15 + 0.6 * age_fraction, 0.6, 0.1, 1.0];
+            let size = leaf.real_size();
+
+            leaf_model(color, Mat4::scale(size, size, size) * leaf.leaf_matrix(branch.real_length(), branch_matrix), plant_vertices, plant_indices);
+        }
+    }
+}
+
+pub fn leaf_model(color: [f32; 4], matrix: Mat4, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) {
+    //A flat diamond in the xy plane, one unit long, drawn from both sides
+    let start_index = vertices.len() as u32;
+
+    for position in [[0.0, 0.0, 0.0], [0.3, 0.5, 0.0], [0.0, 1.0, 0.0], [-0.3, 0.5, 0.0]] {
+        vertices.push(
+            Vertex {
+                position: matrix.mul_vec3_as_slice(position),
+                color: color.clone()
+            });
+    }
+
+    const INDICES: &[u32] = &[
+        0, 1, 2,
+        0, 2, 3,
+
+        0, 2, 1,
+        0, 3, 2
+    ];
+
+    for x in INDICES {
+        indices.push(*x + start_index);
+    }
 }
 
 pub fn branch_model_simple_line(color: [f32; 4], matrix: Mat4, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) {
@@ -0,0 +1,103 @@
+use glium::DrawParameters;
+use crate::glium::Surface;
+
+use crate::herbivore::herbivore::Herbivore;
+use crate::herbivore::herbivore_vec::HerbivoreVec;
+use crate::render::Vertex;
+use crate::render::camera::CameraState;
+use crate::render::mat4_def::Mat4;
+use crate::terrain::{Terrain, TERRAIN_CELL_WIDTH};
+
+// Herbivores are few and always moving, so their models are rebuilt from scratch every frame instead of being cached like plant models
+
+const HERBIVORE_BODY_COLOR: [f32; 4] = [0.45, 0.30, 0.20, 1.0];
+const HERBIVORE_LEG_COLOR: [f32; 4] = [0.30, 0.20, 0.12, 1.0];
+
+impl Herbivore {
+    pub fn push_model(&self, terrain: &Terrain, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) {
+        //Boxes for a body, a head and four legs, standing on the ground and facing along the heading
+        let matrix = Mat4::rotation_y(-self.heading) * Mat4::translation(self.x * TERRAIN_CELL_WIDTH, terrain.get_height(self.x, self.z), self.z * TERRAIN_CELL_WIDTH);
+
+        box_model(HERBIVORE_BODY_COLOR, Mat4::scale(7.0, 3.5, 4.0) * Mat4::translation(0.0, 4.0, 0.0) * matrix.clone(), vertices, indices);
+        box_model(HERBIVORE_BODY_COLOR, Mat4::scale(2.5, 2.5, 2.5) * Mat4::translation(4.5, 6.5, 0.0) * matrix.clone(), vertices, indices);
+
+        for (leg_x, leg_z) in [(-2.5, -1.5), (-2.5, 1.5), (2.5, -1.5), (2.5, 1.5)] {
+            box_model(HERBIVORE_LEG_COLOR, Mat4::scale(0.8, 2.5, 0.8) * Mat4::translation(leg_x, 1.25, leg_z) * matrix.clone(), vertices, indices);
+        }
+    }
+}
+
+impl HerbivoreVec {
+    pub fn render(
+        &self,
+        terrain: &Terrain,
+        target: &mut glium::Frame,
+        program: &glium::Program,
+        display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>,
+        camera: &CameraState,
+        params: &DrawParameters
+    ) {
+        if self.herbivores.is_empty() {
+            return;
+        }
+
+        let mut vertices: Vec<Vertex> = vec![];
+        let mut indices: Vec<u32> = vec![];
+        for herbivore in &self.herbivores {
+            herbivore.push_model(terrain, &mut vertices, &mut indices);
+        }
+
+        let vertex_buffer = glium::VertexBuffer::new(display, &vertices).unwrap();
+        let index_buffer = glium::IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices).unwrap();
+
+        let uniforms = uniform! {
+            view: camera.get_view(),
+            perspective: camera.get_perspective().0,
+            model: Mat4::identity().0,
+
+            is_plant: false
+        };
+
+        target.draw(&vertex_buffer, &index_buffer, program, &uniforms, params).unwrap();
+    }
+}
+
+pub fn box_model(color: [f32; 4], matrix: Mat4, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) {
+    //A unit cube centred on the origin
+    let start_index = vertices.len() as u32;
+
+    for corner in [
+        [-0.5, -0.5, -0.5], [0.5, -0.5, -0.5], [0.5, -0.5, 0.5], [-0.5, -0.5, 0.5],
+        [-0.5, 0.5, -0.5], [0.5, 0.5, -0.5], [0.5, 0.5, 0.5], [-0.5, 0.5, 0.5]
+    ] {
+        vertices.push(
+            Vertex {
+                position: matrix.mul_vec3_as_slice(corner),
+                color: color.clone()
+            });
+    }
+
+    const INDICES: &[u32] = &[
+        0, 1, 2,
+        0, 2, 3,
+
+        4, 6, 5,
+        4, 7, 6,
+
+        0, 4, 5,
+        0, 5, 1,
+
+        1, 5, 6,
+        1, 6, 2,
+
+        2, 6, 7,
+        2, 7, 3,
+
+        3, 7, 4,
+        3, 4, 0
+    ];
+
+    for x in INDICES {
+        indices.push(*x + start_index);
+    }
+}
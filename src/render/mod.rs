@@ -3,5 +3,6 @@ pub mod camera;
 pub mod vector_math;
 pub mod branch_model;
 pub mod mat4_def;
+pub mod herbivore_model;
 
 pub use vertex_def::Vertex;
\ No newline at end of file
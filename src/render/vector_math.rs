@@ -28,6 +28,13 @@ pub fn len_xz(v: (f32, f32, f32)) -> f32 {
     return (v.0*v.0 + v.2*v.2).sqrt();
 }
 
+pub fn dist_xz_wrapped(a: (f32, f32, f32), b: (f32, f32, f32), period: f32) -> f32 {
+    //Shortest xz distance on a world that wraps around every `period` units in both directions
+    let dx = (a.0 - b.0).rem_euclid(period);
+    let dz = (a.2 - b.2).rem_euclid(period);
+    return len_xz((dx.min(period - dx), 0.0, dz.min(period - dz)));
+}
+
 pub fn cross(a: (f32, f32, f32), b: (f32, f32, f32)) -> (f32, f32, f32) {
     return (a.1*b.2 - a.2*b.1, a.2*b.0 - a.0*b.2, a.0*b.1 - a.1*b.0)
 }
@@ -1,4 +1,6 @@
 mod terrain;
 pub mod generate_terrain_mesh;
+pub mod soil_moisture;
+pub mod nutrients;
 
-pub use terrain::{Terrain, TERRAIN_CELL_WIDTH, TERRAIN_GRID_ROWS, WORLD_WIDTH};
\ No newline at end of file
+pub use terrain::{Terrain, TerrainGridNode, GroundType, TERRAIN_CELL_WIDTH, TERRAIN_GRID_ROWS, WORLD_WIDTH};
\ No newline at end of file
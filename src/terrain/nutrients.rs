@@ -0,0 +1,42 @@
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+
+// Nitrogen in the soil gets built into plants as they grow, and goes back into the ground as detritus when branches, roots
+// or whole plants die. Detritus slowly rots back into nitrogen, faster in moist soil, so old forests leave rich patches behind.
+
+pub const NUTRIENT_TICK_MOD: u64 = 30;      // Nutrients get updated once every this many world ticks
+pub const NITROGEN_PER_COST: f32 = 0.05;    // Nitrogen built into a branch or root per point of energy it costs
+
+// All per world tick
+const NITROGEN_DEPOSITION: f32 = 0.0005;    // From the air and the rock, the same everywhere
+const NITROGEN_LEACHING: f32 = 0.0001;      // Fraction of a node's nitrogen washed away
+const DECOMPOSITION_RATE: f32 = 0.0004;     // Fraction of a node's detritus rotting into nitrogen, in completely soaked soil
+
+impl Terrain {
+    pub fn tick_nutrients(&mut self) {
+        let ticks = NUTRIENT_TICK_MOD as f32;
+
+        for x in 0..TERRAIN_GRID_ROWS {
+            for z in 0..TERRAIN_GRID_ROWS {
+                let grid_node = &mut self.grid[x][z];
+
+                let decomposed = grid_node.detritus * (ticks * DECOMPOSITION_RATE * (0.25 + 0.75 * grid_node.soil_moisture)).min(1.0);
+                grid_node.detritus -= decomposed;
+
+                grid_node.nitrogen += decomposed + ticks * (NITROGEN_DEPOSITION - NITROGEN_LEACHING * grid_node.nitrogen);
+                grid_node.nitrogen = grid_node.nitrogen.max(0.0);
+            }
+        }
+    }
+
+    pub fn draw_nitrogen(&mut self, x: f32, z: f32, nitrogen: f32) -> f32 {
+        //Takes up to nitrogen out of the soil under (x, z), returning how much there actually was to take
+        let grid_node = self.get_grid_node_mut(x, z);
+        let drawn = nitrogen.min(grid_node.nitrogen).max(0.0);
+        grid_node.nitrogen -= drawn;
+        return drawn;
+    }
+
+    pub fn add_detritus(&mut self, x: f32, z: f32, nitrogen: f32) {
+        self.get_grid_node_mut(x, z).detritus += nitrogen.max(0.0);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+
+// Soil moisture is the fraction (0 to 1) of the water a grid node's soil can hold that it's holding right now.
+// It soaks in from the water level and the rain, evaporates off dry ground, runs off downhill and gets drunk by plants.
+
+pub const SOIL_MOISTURE_TICK_MOD: u64 = 30;         // Soil moisture gets updated once every this many world ticks
+pub const WATER_PER_SOIL_MOISTURE: f32 = 100.0;     // Plant water in a completely soaked grid node
+
+// All per world tick
+const GROUNDWATER_RECHARGE: f32 = 0.004;            // At the water line, falling off with height above it
+const GROUNDWATER_FALLOFF_HEIGHT: f32 = 10.0;
+const RAIN_RECHARGE: f32 = 0.0005;                  // Scaled by how wet the node is right now (1 - dryness)
+const EVAPORATION: f32 = 0.0006;                    // Scaled by dryness
+const RUNOFF_RATE: f32 = 0.0002;                    // Per unit of gradient
+const MAX_RUNOFF: f32 = 0.2;                        // Most of a node's moisture that can run off in one update
+
+impl Terrain {
+    pub fn tick_soil_moisture(&mut self) {
+        let ticks = SOIL_MOISTURE_TICK_MOD as f32;
+        let mut runoff_in: Vec<f32> = vec![0.0; TERRAIN_GRID_ROWS * TERRAIN_GRID_ROWS];
+
+        for x in 0..TERRAIN_GRID_ROWS {
+            for z in 0..TERRAIN_GRID_ROWS {
+                let grid_node = &mut self.grid[x][z];
+
+                let height_above_water = grid_node.height - self.water_height;
+                if height_above_water <= 0.0 {
+                    //Ground under the water is soaked through
+                    grid_node.soil_moisture = 1.0;
+                    continue;
+                }
+
+                let dryness = grid_node.current_dryness();
+                let recharge = GROUNDWATER_RECHARGE / (1.0 + height_above_water / GROUNDWATER_FALLOFF_HEIGHT) + RAIN_RECHARGE * (1.0 - dryness);
+                grid_node.soil_moisture += ticks * (recharge * (1.0 - grid_node.soil_moisture) - EVAPORATION * dryness * grid_node.soil_moisture);
+                grid_node.soil_moisture = grid_node.soil_moisture.clamp(0.0, 1.0);
+
+                //gradient points uphill, so the water runs off to the neighbours in the opposite direction
+                let (x_grad, z_grad) = grid_node.gradient;
+                let steepness = x_grad.abs() + z_grad.abs();
+                if steepness <= 0.0 {
+                    continue;
+                }
+
+                let runoff = grid_node.soil_moisture * (RUNOFF_RATE * steepness * ticks).min(MAX_RUNOFF);
+                grid_node.soil_moisture -= runoff;
+
+                let downhill_x = (x as isize - x_grad.signum() as isize).rem_euclid(TERRAIN_GRID_ROWS as isize) as usize;
+                let downhill_z = (z as isize - z_grad.signum() as isize).rem_euclid(TERRAIN_GRID_ROWS as isize) as usize;
+                runoff_in[downhill_x * TERRAIN_GRID_ROWS + z] += runoff * x_grad.abs() / steepness;
+                runoff_in[x * TERRAIN_GRID_ROWS + downhill_z] += runoff * z_grad.abs() / steepness;
+            }
+        }
+
+        for x in 0..TERRAIN_GRID_ROWS {
+            for z in 0..TERRAIN_GRID_ROWS {
+                //Whatever doesn't fit in the soil is lost as surface water
+                let grid_node = &mut self.grid[x][z];
+                grid_node.soil_moisture = (grid_node.soil_moisture + runoff_in[x * TERRAIN_GRID_ROWS + z]).min(1.0);
+            }
+        }
+    }
+
+    pub fn draw_water(&mut self, x: f32, z: f32, water: f32) -> f32 {
+        //Takes up to water (in plant water units) out of the soil under (x, z), returning how much there actually was to take
+        let grid_node = self.get_grid_node_mut(x, z);
+        let drawn = water.min(grid_node.soil_moisture * WATER_PER_SOIL_MOISTURE).max(0.0);
+        grid_node.soil_moisture -= drawn / WATER_PER_SOIL_MOISTURE;
+        return drawn;
+    }
+}
@@ -11,6 +11,8 @@ use crate::render::{camera::CameraState, Vertex};
 use glium::{DrawParameters, Surface};
 
 use rand::{Rng, RngCore};
+
+use crate::world::WorldRng;
 use noise::Perlin;
 use crate::noise::NoiseFn;
 
@@ -66,8 +68,19 @@ impl GroundType {
         }
     }
 
-    pub fn random() -> GroundType {
-        match rand::rng().random_range(0..5) {
+    pub fn water_retention(&self) -> f32 {
+        //How much of the water in the ground roots can actually get at
+        match self {
+            Self::Grass => 1.0,
+            Self::Rock  => 0.4,
+            Self::Sand  => 0.5,
+            Self::Swamp => 1.5,
+            Self::Snow  => 0.3
+        }
+    }
+
+    pub fn random(rng: &mut WorldRng) -> GroundType {
+        match rng.random_range(0..5) {
             0 => Self::Grass,
             1 => Self::Rock,
             2 => Self::Sand,
@@ -107,7 +120,18 @@ pub struct TerrainGridNode {
     pub ground_type: GroundType,
 
     pub dryness: f32,
-    pub rockiness: f32
+    pub drought: f32,               // Extra dryness from droughts, fades back to 0 once they end
+    pub rockiness: f32,
+    pub soil_moisture: f32,
+
+    pub nitrogen: f32,
+    pub detritus: f32               // Nitrogen still locked up in dead plant matter
+}
+
+impl TerrainGridNode {
+    pub fn current_dryness(&self) -> f32 {
+        return (self.dryness + self.drought).clamp(0.0, 1.0);
+    }
 }
 
 pub struct Terrain {
@@ -123,8 +147,49 @@ pub struct Terrain {
 
 impl Terrain {
     pub fn get_height(&self, x: f32, z: f32) -> f32 {
-        let x_mapped = (x as f32).rem_euclid(TERRAIN_GRID_ROWS as f32);
-        let z_mapped = (z as f32).rem_euclid(TERRAIN_GRID_ROWS as f32);
+        return self.sample(x, z, |node| node.height);
+    }
+
+    pub fn get_dryness(&self, x: f32, z: f32) -> f32 {
+        return self.sample(x, z, |node| node.current_dryness());
+    }
+
+    pub fn get_rockiness(&self, x: f32, z: f32) -> f32 {
+        return self.sample(x, z, |node| node.rockiness);
+    }
+
+    pub fn get_waterlog(&self, x: f32, z: f32) -> f32 {
+        //How far under the current water level the ground is, negative when it is above the water
+        return self.water_height - self.get_height(x, z);
+    }
+
+    fn grid_indices(x: f32, z: f32) -> (usize, usize) {
+        //The node whose cell (x, z) falls in
+        let x_index = (x.rem_euclid(TERRAIN_GRID_ROWS as f32).floor() as usize).min(TERRAIN_GRID_ROWS - 1);
+        let z_index = (z.rem_euclid(TERRAIN_GRID_ROWS as f32).floor() as usize).min(TERRAIN_GRID_ROWS - 1);
+        return (x_index, z_index);
+    }
+
+    pub fn get_grid_node(&self, x: f32, z: f32) -> &TerrainGridNode {
+        let (x_index, z_index) = Self::grid_indices(x, z);
+        return &self.grid[x_index][z_index];
+    }
+
+    pub fn get_grid_node_mut(&mut self, x: f32, z: f32) -> &mut TerrainGridNode {
+        let (x_index, z_index) = Self::grid_indices(x, z);
+        return &mut self.grid[x_index][z_index];
+    }
+
+    pub fn get_water_availability(&self, x: f32, z: f32) -> f32 {
+        //How easily roots at (x, z) can draw water: moist soil of a kind that gives it up easily
+        let grid_node = self.get_grid_node(x, z);
+        return grid_node.ground_type.water_retention() * 1.5 * grid_node.soil_moisture;
+    }
+
+    fn sample(&self, x: f32, z: f32, field: fn(&TerrainGridNode) -> f32) -> f32 {
+        //Bilinear interpolation of one grid node field between the four nodes surrounding (x, z), wrapping around the world edges
+        let x_mapped = x.rem_euclid(TERRAIN_GRID_ROWS as f32);
+        let z_mapped = z.rem_euclid(TERRAIN_GRID_ROWS as f32);
 
         let neg_x_index = x_mapped.floor() as usize;
         let pos_x_index = (neg_x_index + 1).rem_euclid(TERRAIN_GRID_ROWS);
@@ -134,15 +199,15 @@ impl Terrain {
         let pos_z_index = (neg_z_index + 1).rem_euclid(TERRAIN_GRID_ROWS);
         let z_offset = z_mapped.rem_euclid(1.0);
 
-        let pos_x_pos_z_height = self.grid[pos_x_index][pos_z_index].height;
-        let neg_x_pos_z_height = self.grid[neg_x_index][pos_z_index].height;
-        let pos_x_neg_z_height = self.grid[pos_x_index][neg_z_index].height;
-        let neg_x_neg_z_height = self.grid[neg_x_index][neg_z_index].height;
+        let pos_x_pos_z_val = field(&self.grid[pos_x_index][pos_z_index]);
+        let neg_x_pos_z_val = field(&self.grid[neg_x_index][pos_z_index]);
+        let pos_x_neg_z_val = field(&self.grid[pos_x_index][neg_z_index]);
+        let neg_x_neg_z_val = field(&self.grid[neg_x_index][neg_z_index]);
         
-        return  pos_x_pos_z_height*(x_offset)*(z_offset) + 
-                neg_x_pos_z_height*(1.0-x_offset)*(z_offset) +
-                pos_x_neg_z_height*(x_offset)*(1.0-z_offset) +
-                neg_x_neg_z_height*(1.0-x_offset)*(1.0-z_offset);
+        return  pos_x_pos_z_val*(x_offset)*(z_offset) + 
+                neg_x_pos_z_val*(1.0-x_offset)*(z_offset) +
+                pos_x_neg_z_val*(x_offset)*(1.0-z_offset) +
+                neg_x_neg_z_val*(1.0-x_offset)*(1.0-z_offset);
     }
 
     pub fn empty() -> Terrain {
@@ -153,7 +218,12 @@ impl Terrain {
             ground_type: GroundType::Grass,
 
             dryness: 0.0,
+            drought: 0.0,
             rockiness: 0.0,
+            soil_moisture: 0.0,
+
+            nitrogen: 0.0,
+            detritus: 0.0
         }; TERRAIN_GRID_ROWS]; TERRAIN_GRID_ROWS]),
         water_height: 0.0,
         vertices: vec![],
@@ -163,9 +233,9 @@ impl Terrain {
         };
     }
 
-    pub fn random() -> Terrain {
+    pub fn random(rng: &mut WorldRng) -> Terrain {
         let mut terrain: Terrain = Terrain::empty();
-        let perlin: Perlin = Perlin::new(rand::prelude::SmallRng::from(rand::SeedableRng::from_os_rng()).next_u32());
+        let perlin: Perlin = Perlin::new(rng.next_u32());
 
         for (x, row) in terrain.grid.iter_mut().enumerate() {
             for (y, grid_node) in row.iter_mut().enumerate() {
@@ -193,7 +263,9 @@ impl Terrain {
                     grid_node.height -= 20.0;
                 }
                 
-                grid_node.ground_type = GroundType::random();
+                grid_node.ground_type = GroundType::random(rng);
+                grid_node.soil_moisture = 1.0 - grid_node.dryness;
+                grid_node.nitrogen = 2.0 + 6.0 * (1.0 - grid_node.dryness);
             }
         }
 
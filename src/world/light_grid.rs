@@ -0,0 +1,73 @@
+use crate::terrain::TERRAIN_GRID_ROWS;
+
+// Coarse voxel grid over the terrain that branches cast shade into. Each column is one terrain cell, split into fixed height layers.
+// Shade is kept in fixed point so that plants taking their old shade back out always leaves exactly what was there before.
+
+pub const LIGHT_GRID_LAYER_COUNT: usize = 32;
+pub const LIGHT_GRID_LAYER_HEIGHT: f32 = 5.0;
+pub const LIGHT_GRID_MIN_HEIGHT: f32 = -20.0;
+
+const SHADE_UNITS: f32 = 1024.0;        // Fixed point units per 1.0 of shade
+const LIGHT_EXTINCTION: f32 = 1.0;      // Light left under s shade is e^(-s * LIGHT_EXTINCTION)
+
+#[derive(Clone, Copy)]
+pub struct ShadeContribution {
+    pub voxel_index: u32,
+    pub amount: u32
+}
+
+pub struct LightGrid {
+    shade: Vec<u32>
+}
+
+impl LightGrid {
+    pub fn empty() -> LightGrid {
+        return LightGrid { shade: vec![0; TERRAIN_GRID_ROWS * TERRAIN_GRID_ROWS * LIGHT_GRID_LAYER_COUNT] };
+    }
+
+    fn column_index(x: f32, z: f32) -> usize {
+        //x and z are in terrain cells, like plant root positions
+        let x_index = (x.rem_euclid(TERRAIN_GRID_ROWS as f32).floor() as usize).min(TERRAIN_GRID_ROWS - 1);
+        let z_index = (z.rem_euclid(TERRAIN_GRID_ROWS as f32).floor() as usize).min(TERRAIN_GRID_ROWS - 1);
+        return (x_index * TERRAIN_GRID_ROWS + z_index) * LIGHT_GRID_LAYER_COUNT;
+    }
+
+    fn layer_index(height: f32) -> usize {
+        return (((height - LIGHT_GRID_MIN_HEIGHT) / LIGHT_GRID_LAYER_HEIGHT).max(0.0) as usize).min(LIGHT_GRID_LAYER_COUNT - 1);
+    }
+
+    pub fn shade_contribution(x: f32, z: f32, height: f32, shade: f32) -> ShadeContribution {
+        return ShadeContribution {
+            voxel_index: (Self::column_index(x, z) + Self::layer_index(height)) as u32,
+            amount: (shade.max(0.0) * SHADE_UNITS).round() as u32
+        };
+    }
+
+    pub fn light_at(&self, x: f32, z: f32, height: f32) -> f32 {
+        //Fraction of full sunlight reaching (x, height, z), after passing through all the shade in the layers above it
+        let column = Self::column_index(x, z);
+
+        let mut shade_above: u64 = 0;
+        for layer in Self::layer_index(height) + 1 .. LIGHT_GRID_LAYER_COUNT {
+            shade_above += self.shade[column + layer] as u64;
+        }
+
+        return (-(shade_above as f32 / SHADE_UNITS) * LIGHT_EXTINCTION).exp();
+    }
+
+    pub fn add(&mut self, contributions: &[ShadeContribution]) {
+        for contribution in contributions {
+            self.shade[contribution.voxel_index as usize] += contribution.amount;
+        }
+    }
+
+    pub fn remove(&mut self, contributions: &[ShadeContribution]) {
+        for contribution in contributions {
+            self.shade[contribution.voxel_index as usize] -= contribution.amount;
+        }
+    }
+
+    pub fn voxel_count() -> usize {
+        return TERRAIN_GRID_ROWS * TERRAIN_GRID_ROWS * LIGHT_GRID_LAYER_COUNT;
+    }
+}
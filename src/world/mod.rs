@@ -1,3 +1,11 @@
 mod world;
+pub mod world_rng;
+pub mod snapshot;
+pub mod light_grid;
+pub mod world_clock;
+pub mod weather;
 
-pub use world::World;
\ No newline at end of file
+pub use world::World;
+pub use world_rng::WorldRng;
+pub use light_grid::LightGrid;
+pub use world_clock::WorldClock;
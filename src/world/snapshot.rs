@@ -0,0 +1,1113 @@
+// Versioned binary snapshots of a whole World, so long runs can be checkpointed and picked back up later.
+// Everything is written little-endian in a fixed order; bump SNAPSHOT_VERSION whenever that order or the saved state changes.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+
+use crate::plant::Plant;
+use crate::plant::branch::{Branch, BranchConnection, BRANCH_MAX_OFFSHOOTS};
+use crate::plant::root::Root;
+use crate::plant::genome::{PlantGenome, GenomeRule, RuleReq, RuleCondition, RuleOutcome, OffshootSelection, Phyllotaxis, MAX_RULE_CONDITION_SIZE};
+use crate::plant::genome_mutation::MutationRates;
+use crate::plant::genome_validation::check_rule_count;
+use crate::plant::genome_crossover::CrossoverStrategy;
+use crate::plant::plant_option_vec::PlantOptionVec;
+use crate::plant::seed::Seed;
+use crate::plant::death::{DeathCause, DeathRecord};
+use crate::plant::hormone::{Hormone, HORMONE_COUNT};
+use crate::plant::leaf::{Leaf, BRANCH_MAX_LEAVES};
+use crate::terrain::{Terrain, TerrainGridNode, GroundType, TERRAIN_GRID_ROWS};
+use crate::terrain::generate_terrain_mesh::generate_terrain_mesh;
+use crate::world::{World, WorldRng, LightGrid, WorldClock};
+use crate::world::light_grid::ShadeContribution;
+use crate::world::weather::{Weather, WeatherEvent, WeatherKind};
+use crate::herbivore::herbivore::Herbivore;
+use crate::herbivore::herbivore_vec::HerbivoreVec;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"PESNAP\0\0";
+pub const SNAPSHOT_VERSION: u32 = 16;
+
+fn invalid_data(message: &str) -> Error {
+    return Error::new(ErrorKind::InvalidData, message.to_string());
+}
+
+pub struct SnapshotWriter {
+    pub bytes: Vec<u8>
+}
+
+impl SnapshotWriter {
+    pub fn new() -> SnapshotWriter {
+        return SnapshotWriter { bytes: vec![] };
+    }
+
+    pub fn write_u8(&mut self, val: u8) {
+        self.bytes.push(val);
+    }
+
+    pub fn write_bool(&mut self, val: bool) {
+        self.write_u8(val as u8);
+    }
+
+    pub fn write_u32(&mut self, val: u32) {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, val: u64) {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, val: f32) {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
+    }
+
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+
+    pub fn write_option_u64(&mut self, val: Option<u64>) {
+        match val {
+            Some(val) => {
+                self.write_bool(true);
+                self.write_u64(val);
+            },
+            None => {
+                self.write_bool(false);
+            }
+        }
+    }
+}
+
+pub struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl<'a> SnapshotReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> SnapshotReader<'a> {
+        return SnapshotReader { bytes, position: 0 };
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        if self.position + N > self.bytes.len() {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "Snapshot ended early"));
+        }
+        let mut out = [0u8; N];
+        out.copy_from_slice(&self.bytes[self.position .. self.position + N]);
+        self.position += N;
+        return Ok(out);
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        return Ok(self.take::<1>()?[0]);
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool> {
+        return Ok(self.read_u8()? != 0);
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32> {
+        return Ok(u32::from_le_bytes(self.take::<4>()?));
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64> {
+        return Ok(u64::from_le_bytes(self.take::<8>()?));
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32> {
+        return Ok(f32::from_le_bytes(self.take::<4>()?));
+    }
+
+    pub fn read_len(&mut self) -> Result<usize> {
+        return Ok(self.read_u32()? as usize);
+    }
+
+    pub fn read_option_u64(&mut self) -> Result<Option<u64>> {
+        return match self.read_bool()? {
+            true => Ok(Some(self.read_u64()?)),
+            false => Ok(None)
+        };
+    }
+}
+
+impl World {
+    pub fn save_snapshot(&self, path: &str) -> Result<()> {
+        return fs::write(path, self.to_snapshot_bytes());
+    }
+
+    pub fn load_snapshot(path: &str) -> Result<World> {
+        return World::from_snapshot_bytes(&fs::read(path)?);
+    }
+
+    pub fn to_snapshot_bytes(&self) -> Vec<u8> {
+        let mut w = SnapshotWriter::new();
+
+        w.bytes.extend_from_slice(SNAPSHOT_MAGIC);
+        w.write_u32(SNAPSHOT_VERSION);
+
+        w.write_u64(self.seed);
+        for word in self.rng.state() {
+            w.write_u64(word);
+        }
+        w.write_u64(self.total_ticks);
+        w.write_u64(self.clock.day_length_ticks);
+        w.write_u64(self.clock.year_length_days);
+        w.write_f32(self.clock.latitude);
+
+        write_terrain(&mut w, &self.terrain);
+        write_weather(&mut w, &self.weather);
+        write_plant_option_vec(&mut w, &self.plants);
+        write_herbivore_vec(&mut w, &self.herbivores);
+
+        return w.bytes;
+    }
+
+    pub fn from_snapshot_bytes(bytes: &[u8]) -> Result<World> {
+        let mut r = SnapshotReader::new(bytes);
+
+        if &r.take::<8>()? != SNAPSHOT_MAGIC {
+            return Err(invalid_data("Not a world snapshot"));
+        }
+        let version = r.read_u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid_data(&format!("Snapshot version {} is not supported (expected {})", version, SNAPSHOT_VERSION)));
+        }
+
+        let seed = r.read_u64()?;
+        let rng = WorldRng::from_state([r.read_u64()?, r.read_u64()?, r.read_u64()?, r.read_u64()?]);
+        let total_ticks = r.read_u64()?;
+        let clock = WorldClock {
+            day_length_ticks: r.read_u64()?,
+            year_length_days: r.read_u64()?,
+            latitude: r.read_f32()?
+        };
+
+        let terrain = read_terrain(&mut r)?;
+        let weather = read_weather(&mut r)?;
+        let plants = read_plant_option_vec(&mut r)?;
+        let herbivores = read_herbivore_vec(&mut r)?;
+
+        //The light grid is nothing but the sum of the shade every plant last cast, so it gets rebuilt rather than saved
+        let mut light_grid = LightGrid::empty();
+        for plant in plants.internal_vec.iter().flatten() {
+            light_grid.add(&plant.shade_contributions);
+        }
+
+        return Ok(World {
+            seed,
+            rng,
+            total_ticks,
+            clock,
+            weather,
+            terrain,
+            light_grid,
+            plants,
+            herbivores
+        });
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Terrain
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn write_terrain(w: &mut SnapshotWriter, terrain: &Terrain) {
+    w.write_len(TERRAIN_GRID_ROWS);
+    w.write_f32(terrain.water_height);
+
+    for row in terrain.grid.iter() {
+        for grid_node in row.iter() {
+            w.write_f32(grid_node.height);
+            w.write_f32(grid_node.local_height);
+            w.write_f32(grid_node.gradient.0);
+            w.write_f32(grid_node.gradient.1);
+            w.write_u8(match grid_node.ground_type {
+                GroundType::Grass => 0,
+                GroundType::Rock => 1,
+                GroundType::Sand => 2,
+                GroundType::Swamp => 3,
+                GroundType::Snow => 4
+            });
+            w.write_f32(grid_node.dryness);
+            w.write_f32(grid_node.drought);
+            w.write_f32(grid_node.rockiness);
+            w.write_f32(grid_node.soil_moisture);
+            w.write_f32(grid_node.nitrogen);
+            w.write_f32(grid_node.detritus);
+        }
+    }
+}
+
+fn read_terrain(r: &mut SnapshotReader) -> Result<Terrain> {
+    if r.read_len()? != TERRAIN_GRID_ROWS {
+        return Err(invalid_data("Snapshot terrain grid size doesn't match TERRAIN_GRID_ROWS"));
+    }
+
+    let mut terrain = Terrain::empty();
+    terrain.water_height = r.read_f32()?;
+
+    for row in terrain.grid.iter_mut() {
+        for grid_node in row.iter_mut() {
+            *grid_node = TerrainGridNode {
+                height: r.read_f32()?,
+                local_height: r.read_f32()?,
+                gradient: (r.read_f32()?, r.read_f32()?),
+                ground_type: match r.read_u8()? {
+                    0 => GroundType::Grass,
+                    1 => GroundType::Rock,
+                    2 => GroundType::Sand,
+                    3 => GroundType::Swamp,
+                    4 => GroundType::Snow,
+                    _ => return Err(invalid_data("Unknown ground type"))
+                },
+                dryness: r.read_f32()?,
+                drought: r.read_f32()?,
+                rockiness: r.read_f32()?,
+                soil_moisture: r.read_f32()?,
+                nitrogen: r.read_f32()?,
+                detritus: r.read_f32()?
+            };
+        }
+    }
+
+    (terrain.vertices, terrain.indices, terrain.water_vertices, terrain.water_indices) = generate_terrain_mesh(&terrain);
+
+    return Ok(terrain);
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Weather
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn write_weather(w: &mut SnapshotWriter, weather: &Weather) {
+    w.write_len(weather.events.len());
+    for event in &weather.events {
+        w.write_u8(match event.kind {
+            WeatherKind::Rain => 0,
+            WeatherKind::Drought => 1,
+            WeatherKind::Storm => 2
+        });
+        w.write_f32(event.x);
+        w.write_f32(event.z);
+        w.write_f32(event.radius);
+        w.write_f32(event.strength);
+        w.write_u64(event.ticks_remaining);
+    }
+}
+
+fn read_weather(r: &mut SnapshotReader) -> Result<Weather> {
+    let event_count = r.read_len()?;
+    let mut weather = Weather::new();
+    for _i in 0..event_count {
+        weather.events.push(WeatherEvent {
+            kind: match r.read_u8()? {
+                0 => WeatherKind::Rain,
+                1 => WeatherKind::Drought,
+                2 => WeatherKind::Storm,
+                _ => return Err(invalid_data("Unknown weather event kind"))
+            },
+            x: r.read_f32()?,
+            z: r.read_f32()?,
+            radius: r.read_f32()?,
+            strength: r.read_f32()?,
+            ticks_remaining: r.read_u64()?
+        });
+    }
+    return Ok(weather);
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Herbivores
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn write_herbivore_vec(w: &mut SnapshotWriter, herbivores: &HerbivoreVec) {
+    w.write_len(herbivores.herbivores.len());
+    for herbivore in &herbivores.herbivores {
+        w.write_f32(herbivore.x);
+        w.write_f32(herbivore.z);
+        w.write_f32(herbivore.heading);
+        w.write_f32(herbivore.energy);
+        w.write_u64(herbivore.age_ticks);
+    }
+}
+
+fn read_herbivore_vec(r: &mut SnapshotReader) -> Result<HerbivoreVec> {
+    let herbivore_count = r.read_len()?;
+    let mut herbivores = HerbivoreVec::new();
+    for _i in 0..herbivore_count {
+        herbivores.herbivores.push(Herbivore {
+            x: r.read_f32()?,
+            z: r.read_f32()?,
+            heading: r.read_f32()?,
+            energy: r.read_f32()?,
+            age_ticks: r.read_u64()?
+        });
+    }
+    return Ok(herbivores);
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Plants and seeds
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+fn write_plant_option_vec(w: &mut SnapshotWriter, plants: &PlantOptionVec) {
+    write_mutation_rates(w, &plants.mutation_rates);
+    w.write_u8(match plants.crossover_strategy {
+        CrossoverStrategy::SinglePoint => 0,
+        CrossoverStrategy::Uniform => 1,
+        CrossoverStrategy::RuleAligned => 2
+    });
+
+    w.write_u64(plants.next_plant_id);
+    w.write_len(plants.internal_vec.len());
+    for (item, generation) in plants.internal_vec.iter().zip(&plants.generations) {
+        w.write_u32(*generation);
+        match item {
+            Some(plant) => {
+                w.write_bool(true);
+                write_plant(w, plant);
+            }
+            None => {
+                w.write_bool(false);
+            }
+        }
+    }
+
+    w.write_len(plants.seeds.len());
+    for seed in &plants.seeds {
+        write_genome(w, &seed.genome);
+        w.write_f32(seed.x);
+        w.write_f32(seed.z);
+        w.write_f32(seed.energy);
+        w.write_u64(seed.ticks_until_germination);
+        w.write_u64(seed.mother_id);
+        w.write_option_u64(seed.father_id);
+    }
+
+    w.write_len(plants.death_records.len());
+    for record in &plants.death_records {
+        w.write_u64(record.plant_id);
+        w.write_option_u64(record.mother_id);
+        w.write_option_u64(record.father_id);
+        w.write_u8(match record.cause {
+            DeathCause::Starvation => 0,
+            DeathCause::OldAge => 1,
+            DeathCause::Drowning => 2,
+            DeathCause::Collapse => 3,
+            DeathCause::Grazing => 4
+        });
+        w.write_u64(record.tick);
+        w.write_u64(record.age_ticks);
+        w.write_f32(record.x);
+        w.write_f32(record.z);
+        w.write_len(record.branch_count);
+        w.write_u32(record.seeds_produced);
+    }
+}
+
+fn read_plant_option_vec(r: &mut SnapshotReader) -> Result<PlantOptionVec> {
+    let mutation_rates = read_mutation_rates(r)?;
+    let crossover_strategy = match r.read_u8()? {
+        0 => CrossoverStrategy::SinglePoint,
+        1 => CrossoverStrategy::Uniform,
+        2 => CrossoverStrategy::RuleAligned,
+        _ => return Err(invalid_data("Unknown crossover strategy"))
+    };
+
+    let next_plant_id = r.read_u64()?;
+    let slot_count = r.read_len()?;
+    let mut internal_vec: Vec<Option<Box<Plant>>> = vec![];
+    let mut generations: Vec<u32> = vec![];
+    for _i in 0..slot_count {
+        generations.push(r.read_u32()?);
+        if r.read_bool()? {
+            internal_vec.push(Some(Box::new(read_plant(r)?)));
+        } else {
+            internal_vec.push(None);
+        }
+    }
+
+    let seed_count = r.read_len()?;
+    let mut seeds: Vec<Seed> = vec![];
+    for _i in 0..seed_count {
+        seeds.push(Seed {
+            genome: read_genome(r)?,
+            x: r.read_f32()?,
+            z: r.read_f32()?,
+            energy: r.read_f32()?,
+            ticks_until_germination: r.read_u64()?,
+            mother_id: r.read_u64()?,
+            father_id: r.read_option_u64()?
+        });
+    }
+
+    let record_count = r.read_len()?;
+    let mut death_records: VecDeque<DeathRecord> = VecDeque::new();
+    for _i in 0..record_count {
+        death_records.push_back(DeathRecord {
+            plant_id: r.read_u64()?,
+            mother_id: r.read_option_u64()?,
+            father_id: r.read_option_u64()?,
+            cause: match r.read_u8()? {
+                0 => DeathCause::Starvation,
+                1 => DeathCause::OldAge,
+                2 => DeathCause::Drowning,
+                3 => DeathCause::Collapse,
+                4 => DeathCause::Grazing,
+                _ => return Err(invalid_data("Unknown death cause"))
+            },
+            tick: r.read_u64()?,
+            age_ticks: r.read_u64()?,
+            x: r.read_f32()?,
+            z: r.read_f32()?,
+            branch_count: r.read_len()?,
+            seeds_produced: r.read_u32()?
+        });
+    }
+
+    let mut plants = PlantOptionVec::from_slots(internal_vec, generations, next_plant_id, seeds, death_records);
+    plants.mutation_rates = mutation_rates;
+    plants.crossover_strategy = crossover_strategy;
+
+    return Ok(plants);
+}
+
+fn write_mutation_rates(w: &mut SnapshotWriter, rates: &MutationRates) {
+    w.write_f32(rates.numeric_jitter_chance);
+    w.write_f32(rates.numeric_jitter_scale);
+    w.write_f32(rates.swap_req_chance);
+    w.write_f32(rates.change_outcome_chance);
+    w.write_f32(rates.combine_condition_chance);
+    w.write_f32(rates.drop_condition_chance);
+    w.write_f32(rates.negate_condition_chance);
+    w.write_f32(rates.insert_rule_chance);
+    w.write_f32(rates.delete_rule_chance);
+    w.write_f32(rates.reorder_rule_chance);
+    w.write_f32(rates.change_phyllotaxis_chance);
+}
+
+fn read_mutation_rates(r: &mut SnapshotReader) -> Result<MutationRates> {
+    return Ok(MutationRates {
+        numeric_jitter_chance: r.read_f32()?,
+        numeric_jitter_scale: r.read_f32()?,
+        swap_req_chance: r.read_f32()?,
+        change_outcome_chance: r.read_f32()?,
+        combine_condition_chance: r.read_f32()?,
+        drop_condition_chance: r.read_f32()?,
+        negate_condition_chance: r.read_f32()?,
+        insert_rule_chance: r.read_f32()?,
+        delete_rule_chance: r.read_f32()?,
+        reorder_rule_chance: r.read_f32()?,
+        change_phyllotaxis_chance: r.read_f32()?
+    });
+}
+
+fn write_plant(w: &mut SnapshotWriter, plant: &Plant) {
+    w.write_u64(plant.id);
+    w.write_option_u64(plant.mother_id);
+    w.write_option_u64(plant.father_id);
+    write_genome(w, &plant.genome);
+    w.write_u64(plant.age_ticks);
+    w.write_u32(plant.seeds_produced);
+    w.write_u32(plant.submerged_ticks);
+
+    w.write_f32(plant.root_position.0);
+    w.write_f32(plant.root_position.1);
+    w.write_f32(plant.root_position.2);
+
+    w.write_len(plant.branches.len());
+    for branch in &plant.branches {
+        write_branch(w, branch);
+    }
+
+    w.write_len(plant.roots.len());
+    for root in &plant.roots {
+        write_root(w, root);
+    }
+
+    w.write_f32(plant.current_energy);
+    w.write_f32(plant.current_water);
+    w.write_f32(plant.current_sunlight);
+    w.write_f32(plant.current_nitrogen);
+
+    w.write_len(plant.shade_contributions.len());
+    for contribution in &plant.shade_contributions {
+        w.write_u32(contribution.voxel_index);
+        w.write_u32(contribution.amount);
+    }
+}
+
+fn read_plant(r: &mut SnapshotReader) -> Result<Plant> {
+    let id = r.read_u64()?;
+    let mother_id = r.read_option_u64()?;
+    let father_id = r.read_option_u64()?;
+    let genome = read_genome(r)?;
+    let age_ticks = r.read_u64()?;
+    let seeds_produced = r.read_u32()?;
+    let submerged_ticks = r.read_u32()?;
+
+    let root_position = (r.read_f32()?, r.read_f32()?, r.read_f32()?);
+
+    let branch_count = r.read_len()?;
+    let mut branches: Vec<Branch> = vec![];
+    for _i in 0..branch_count {
+        branches.push(read_branch(r, branch_count)?);
+    }
+    check_branch_tree(&branches)?;
+
+    let root_count = r.read_len()?;
+    let mut roots: Vec<Root> = vec![];
+    for root_index in 0..root_count {
+        roots.push(read_root(r, root_index)?);
+    }
+
+    let current_energy = r.read_f32()?;
+    let current_water = r.read_f32()?;
+    let current_sunlight = r.read_f32()?;
+    let current_nitrogen = r.read_f32()?;
+
+    let contribution_count = r.read_len()?;
+    let mut shade_contributions: Vec<ShadeContribution> = vec![];
+    for _i in 0..contribution_count {
+        let voxel_index = r.read_u32()?;
+        if voxel_index as usize >= LightGrid::voxel_count() {
+            return Err(invalid_data("Shade contribution outside of the light grid"));
+        }
+        shade_contributions.push(ShadeContribution { voxel_index, amount: r.read_u32()? });
+    }
+
+    return Ok(Plant {
+        id,
+        mother_id,
+        father_id,
+        genome,
+        age_ticks,
+        seeds_produced,
+        submerged_ticks,
+
+        root_position,
+        branches,
+        branch_kill_requests: vec![],
+        leaf_shed_requests: vec![],
+        shade_contributions,
+
+        roots,
+        root_kill_requested: false,
+
+        current_energy,
+        current_water,
+        current_sunlight,
+        current_nitrogen,
+
+        pending_mesh: None,
+        cached_model: None
+    });
+}
+
+fn check_branch_tree(branches: &[Branch]) -> Result<()> {
+    //Growing and rendering walk the branches recursively from the trunk at index 0, so they have to form a tree that reaches every branch exactly once
+    if branches.is_empty() {
+        return Err(invalid_data("Plant has no branches"));
+    }
+
+    let mut reached = vec![false; branches.len()];
+    reached[0] = true;
+    let mut to_visit: Vec<usize> = vec![0];
+    while let Some(branch_index) = to_visit.pop() {
+        for connection in &branches[branch_index].offshoots {
+            if reached[connection.branch_index] {
+                return Err(invalid_data("Branch is reached more than once from the trunk"));
+            }
+            reached[connection.branch_index] = true;
+            to_visit.push(connection.branch_index);
+        }
+    }
+
+    if reached.contains(&false) {
+        return Err(invalid_data("Branch isn't connected to the trunk"));
+    }
+    return Ok(());
+}
+
+fn write_branch(w: &mut SnapshotWriter, branch: &Branch) {
+    w.write_f32(branch.strength);
+    w.write_f32(branch.photoreceptiveness);
+    w.write_f32(branch.water_intake);
+    w.write_f32(branch.length);
+    for level in branch.hormones {
+        w.write_f32(level);
+    }
+    w.write_f32(branch.collected_sunlight);
+
+    w.write_len(branch.offshoots.len());
+    for connection in &branch.offshoots {
+        write_branch_connection(w, connection);
+    }
+    w.write_len(branch.leaves.len());
+    for leaf in &branch.leaves {
+        write_leaf(w, leaf);
+    }
+}
+
+fn read_branch(r: &mut SnapshotReader, branch_count: usize) -> Result<Branch> {
+    return Ok(Branch {
+        strength: r.read_f32()?,
+        photoreceptiveness: r.read_f32()?,
+        water_intake: r.read_f32()?,
+        length: r.read_f32()?,
+        hormones: read_hormone_levels(r)?,
+        collected_sunlight: r.read_f32()?,
+
+        offshoots: read_branch_connections(r, branch_count)?,
+        leaves: read_leaves(r)?
+    });
+}
+
+fn read_hormone_levels(r: &mut SnapshotReader) -> Result<[f32; HORMONE_COUNT]> {
+    let mut hormones = [0.0; HORMONE_COUNT];
+    for level in hormones.iter_mut() {
+        *level = r.read_f32()?;
+    }
+    return Ok(hormones);
+}
+
+fn write_leaf(w: &mut SnapshotWriter, leaf: &Leaf) {
+    w.write_f32(leaf.area);
+    w.write_f32(leaf.yaw);
+    w.write_f32(leaf.pitch);
+    w.write_f32(leaf.along_length);
+    w.write_f32(leaf.lifespan);
+    w.write_u32(leaf.age_ticks);
+}
+
+fn read_leaves(r: &mut SnapshotReader) -> Result<Vec<Leaf>> {
+    let leaf_count = r.read_len()?;
+    if leaf_count > BRANCH_MAX_LEAVES {
+        return Err(invalid_data("Branch has more leaves than a branch can hold"));
+    }
+
+    let mut leaves: Vec<Leaf> = vec![];
+    for _i in 0..leaf_count {
+        leaves.push(Leaf {
+            area: r.read_f32()?,
+            yaw: r.read_f32()?,
+            pitch: r.read_f32()?,
+            along_length: r.read_f32()?,
+            lifespan: r.read_f32()?,
+            age_ticks: r.read_u32()?
+        });
+    }
+    return Ok(leaves);
+}
+
+fn write_root(w: &mut SnapshotWriter, root: &Root) {
+    match root.parent {
+        Some(parent_index) => {
+            w.write_bool(true);
+            w.write_len(parent_index);
+        },
+        None => {
+            w.write_bool(false);
+        }
+    }
+    w.write_f32(root.yaw);
+    w.write_f32(root.pitch);
+    w.write_f32(root.water_intake);
+    w.write_f32(root.length);
+}
+
+fn read_root(r: &mut SnapshotReader, root_index: usize) -> Result<Root> {
+    //Roots only grow off of older roots, so a parent always comes before its children
+    let parent = match r.read_bool()? {
+        true => {
+            let parent_index = r.read_len()?;
+            if parent_index >= root_index {
+                return Err(invalid_data("Root grows off of a root that comes after it"));
+            }
+            Some(parent_index)
+        },
+        false => None
+    };
+
+    return Ok(Root {
+        parent,
+        yaw: r.read_f32()?,
+        pitch: r.read_f32()?,
+        water_intake: r.read_f32()?,
+        length: r.read_f32()?
+    });
+}
+
+fn write_branch_connection(w: &mut SnapshotWriter, connection: &BranchConnection) {
+    w.write_len(connection.branch_index);
+    w.write_f32(connection.yaw);
+    w.write_f32(connection.pitch);
+    w.write_f32(connection.along_length);
+    w.write_f32(connection.droop);
+}
+
+fn read_branch_connections(r: &mut SnapshotReader, branch_count: usize) -> Result<Vec<BranchConnection>> {
+    let offshoot_count = r.read_len()?;
+    if offshoot_count > BRANCH_MAX_OFFSHOOTS {
+        return Err(invalid_data("Branch has more offshoots than a branch can grow"));
+    }
+
+    let mut connections: Vec<BranchConnection> = Vec::with_capacity(offshoot_count);
+    for _i in 0..offshoot_count {
+        let branch_index = r.read_len()?;
+        if branch_index >= branch_count {
+            return Err(invalid_data("Branch connection points past the end of the plant's branches"));
+        }
+
+        connections.push(BranchConnection {
+            branch_index,
+            yaw: r.read_f32()?,
+            pitch: r.read_f32()?,
+            along_length: r.read_f32()?,
+            droop: r.read_f32()?
+        });
+    }
+    return Ok(connections);
+}
+
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+// Genomes
+/////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub fn write_genome(w: &mut SnapshotWriter, genome: &PlantGenome) {
+    w.write_f32(genome.min_enegy_for_growth);
+    w.write_f32(genome.baby_energy);
+    w.write_f32(genome.lifespan);
+    w.write_f32(genome.pollination_range);
+
+    w.write_f32(genome.sapling_strength);
+    w.write_f32(genome.sapling_photoreceptiveness);
+    w.write_f32(genome.sapling_water_intake);
+    w.write_f32(genome.sapling_length);
+
+    w.write_f32(genome.max_offshoots);
+    w.write_f32(genome.offshoot_pitch);
+    w.write_u8(Phyllotaxis::ALL.iter().position(|other| *other == genome.phyllotaxis).unwrap() as u8);
+
+    w.write_len(genome.rules.len());
+    for rule in &genome.rules {
+        write_rule_condition(w, &rule.condition);
+        write_rule_outcome(w, &rule.outcome);
+    }
+}
+
+pub fn read_genome(r: &mut SnapshotReader) -> Result<PlantGenome> {
+    let mut genome = PlantGenome {
+        min_enegy_for_growth: r.read_f32()?,
+        baby_energy: r.read_f32()?,
+        lifespan: r.read_f32()?,
+        pollination_range: r.read_f32()?,
+
+        sapling_strength: r.read_f32()?,
+        sapling_photoreceptiveness: r.read_f32()?,
+        sapling_water_intake: r.read_f32()?,
+        sapling_length: r.read_f32()?,
+
+        max_offshoots: r.read_f32()?,
+        offshoot_pitch: r.read_f32()?,
+        phyllotaxis: match Phyllotaxis::ALL.get(r.read_u8()? as usize) {
+            Some(phyllotaxis) => *phyllotaxis,
+            None => return Err(invalid_data("Unknown phyllotaxis"))
+        },
+
+        rules: vec![]
+    };
+
+    let rule_count = r.read_len()?;
+    check_rule_count(rule_count).map_err(|message| invalid_data(&message))?;
+    for _i in 0..rule_count {
+        let mut nodes_left = MAX_RULE_CONDITION_SIZE;
+        genome.rules.push(GenomeRule {
+            condition: read_rule_condition(r, &mut nodes_left)?,
+            outcome: read_rule_outcome(r)?
+        });
+    }
+
+    genome.validate().map_err(|message| invalid_data(&message))?;
+    return Ok(genome);
+}
+
+fn write_rule_condition(w: &mut SnapshotWriter, condition: &RuleCondition) {
+    match condition {
+        RuleCondition::Req { req, min, max } => {
+            w.write_u8(0);
+            write_rule_req(w, req);
+            w.write_f32(*min);
+            w.write_f32(*max);
+        },
+        RuleCondition::Not(condition) => {
+            w.write_u8(1);
+            write_rule_condition(w, condition);
+        },
+        RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
+            w.write_u8(match condition {
+                RuleCondition::And(_) => 2,
+                _ => 3
+            });
+            w.write_len(conditions.len());
+            for condition in conditions {
+                write_rule_condition(w, condition);
+            }
+        }
+    }
+}
+
+fn read_rule_condition(r: &mut SnapshotReader, nodes_left: &mut usize) -> Result<RuleCondition> {
+    //nodes_left keeps a corrupt snapshot from describing an endlessly deep condition
+    if *nodes_left == 0 {
+        return Err(invalid_data("Rule condition is too big"));
+    }
+    *nodes_left -= 1;
+
+    match r.read_u8()? {
+        0 => Ok(RuleCondition::Req {
+            req: read_rule_req(r)?,
+            min: r.read_f32()?,
+            max: r.read_f32()?
+        }),
+        1 => Ok(RuleCondition::Not(Box::new(read_rule_condition(r, nodes_left)?))),
+        kind @ (2 | 3) => {
+            let term_count = r.read_len()?;
+            if term_count < 2 || term_count > *nodes_left {
+                return Err(invalid_data("Rule condition has the wrong number of terms"));
+            }
+            let mut conditions: Vec<RuleCondition> = Vec::with_capacity(term_count);
+            for _i in 0..term_count {
+                conditions.push(read_rule_condition(r, nodes_left)?);
+            }
+            Ok(match kind {
+                2 => RuleCondition::And(conditions),
+                _ => RuleCondition::Or(conditions)
+            })
+        },
+        _ => Err(invalid_data("Unknown rule condition kind"))
+    }
+}
+
+fn write_rule_req(w: &mut SnapshotWriter, req: &RuleReq) {
+    w.write_u8(RuleReq::ALL.iter().position(|other| other == req).unwrap() as u8);
+}
+
+fn read_rule_req(r: &mut SnapshotReader) -> Result<RuleReq> {
+    match RuleReq::ALL.get(r.read_u8()? as usize) {
+        Some(req) => Ok(*req),
+        None => Err(invalid_data("Unknown rule requirement"))
+    }
+}
+
+fn write_rule_outcome(w: &mut SnapshotWriter, outcome: &RuleOutcome) {
+    match outcome {
+        RuleOutcome::Exit => {
+            w.write_u8(0);
+        },
+        RuleOutcome::KillOffshoot(offshoot_selection) => {
+            w.write_u8(1);
+            w.write_u8(match offshoot_selection {
+                OffshootSelection::One => 0,
+                OffshootSelection::Two => 1,
+                OffshootSelection::Longest => 2,
+                OffshootSelection::Weakest => 3,
+                OffshootSelection::LeastPhotoreceptive => 4,
+                OffshootSelection::MostCostlyPerYield => 5,
+                OffshootSelection::Random => 6
+            });
+        },
+        RuleOutcome::RequestModifyBranch {
+            priority,
+            strength_factor,
+            photoreceptiveness_factor,
+            water_intake_factor,
+            length_factor
+        } => {
+            w.write_u8(2);
+            w.write_f32(*priority);
+            w.write_f32(*strength_factor);
+            w.write_f32(*photoreceptiveness_factor);
+            w.write_f32(*water_intake_factor);
+            w.write_f32(*length_factor);
+        },
+        RuleOutcome::RequestNewOffshoot {
+            priority,
+            placement_straightness,
+            strength,
+            photoreceptiveness,
+            water_intake,
+            length
+        } => {
+            w.write_u8(3);
+            w.write_f32(*priority);
+            w.write_f32(*placement_straightness);
+            w.write_f32(*strength);
+            w.write_f32(*photoreceptiveness);
+            w.write_f32(*water_intake);
+            w.write_f32(*length);
+        },
+        RuleOutcome::RequestNewRoot {
+            priority,
+            spread,
+            water_intake,
+            length
+        } => {
+            w.write_u8(4);
+            w.write_f32(*priority);
+            w.write_f32(*spread);
+            w.write_f32(*water_intake);
+            w.write_f32(*length);
+        },
+        RuleOutcome::KillRoot => {
+            w.write_u8(5);
+        },
+        RuleOutcome::ProduceHormone { hormone, amount } => {
+            w.write_u8(6);
+            w.write_u8(hormone.index() as u8);
+            w.write_f32(*amount);
+        },
+        RuleOutcome::RequestNewLeaf {
+            priority,
+            area,
+            angle,
+            lifespan
+        } => {
+            w.write_u8(7);
+            w.write_f32(*priority);
+            w.write_f32(*area);
+            w.write_f32(*angle);
+            w.write_f32(*lifespan);
+        },
+        RuleOutcome::ShedLeaves => {
+            w.write_u8(8);
+        }
+    }
+}
+
+fn read_rule_outcome(r: &mut SnapshotReader) -> Result<RuleOutcome> {
+    match r.read_u8()? {
+        0 => Ok(RuleOutcome::Exit),
+        1 => Ok(RuleOutcome::KillOffshoot(match r.read_u8()? {
+            0 => OffshootSelection::One,
+            1 => OffshootSelection::Two,
+            2 => OffshootSelection::Longest,
+            3 => OffshootSelection::Weakest,
+            4 => OffshootSelection::LeastPhotoreceptive,
+            5 => OffshootSelection::MostCostlyPerYield,
+            6 => OffshootSelection::Random,
+            _ => return Err(invalid_data("Unknown offshoot selection"))
+        })),
+        2 => Ok(RuleOutcome::RequestModifyBranch {
+            priority: r.read_f32()?,
+            strength_factor: r.read_f32()?,
+            photoreceptiveness_factor: r.read_f32()?,
+            water_intake_factor: r.read_f32()?,
+            length_factor: r.read_f32()?
+        }),
+        3 => Ok(RuleOutcome::RequestNewOffshoot {
+            priority: r.read_f32()?,
+            placement_straightness: r.read_f32()?,
+            strength: r.read_f32()?,
+            photoreceptiveness: r.read_f32()?,
+            water_intake: r.read_f32()?,
+            length: r.read_f32()?
+        }),
+        4 => Ok(RuleOutcome::RequestNewRoot {
+            priority: r.read_f32()?,
+            spread: r.read_f32()?,
+            water_intake: r.read_f32()?,
+            length: r.read_f32()?
+        }),
+        5 => Ok(RuleOutcome::KillRoot),
+        6 => Ok(RuleOutcome::ProduceHormone {
+            hormone: match Hormone::ALL.get(r.read_u8()? as usize) {
+                Some(hormone) => *hormone,
+                None => return Err(invalid_data("Unknown hormone"))
+            },
+            amount: r.read_f32()?
+        }),
+        7 => Ok(RuleOutcome::RequestNewLeaf {
+            priority: r.read_f32()?,
+            area: r.read_f32()?,
+            angle: r.read_f32()?,
+            lifespan: r.read_f32()?
+        }),
+        8 => Ok(RuleOutcome::ShedLeaves),
+        _ => Err(invalid_data("Unknown rule outcome"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running_world() -> World {
+        let mut world = World::world_init(7);
+        world.spawn_random_plants(60, &PlantGenome::human_made_tree_genome());
+        world.spawn_random_herbivores(5);
+        for _i in 0..300 {
+            world.tick(None);
+        }
+        return world;
+    }
+
+    #[test]
+    fn running_world_round_trips() {
+        let mut world = running_world();
+        assert!(world.plants.internal_vec.iter().flatten().count() > 0);
+
+        let bytes = world.to_snapshot_bytes();
+        let mut loaded = World::from_snapshot_bytes(&bytes).unwrap();
+        assert!(loaded.to_snapshot_bytes() == bytes);
+
+        //The loaded world has to keep playing out exactly like the one it was saved from
+        for _i in 0..50 {
+            world.tick(None);
+            loaded.tick(None);
+        }
+        assert!(loaded.to_snapshot_bytes() == world.to_snapshot_bytes());
+    }
+
+    #[test]
+    fn rejects_truncated_snapshot() {
+        let bytes = running_world().to_snapshot_bytes();
+        for len in [0, 4, 12, bytes.len() / 2, bytes.len() - 1] {
+            match World::from_snapshot_bytes(&bytes[..len]) {
+                Ok(_) => panic!("Snapshot cut to {} of {} bytes loaded", len, bytes.len()),
+                Err(error) => assert!(error.kind() == ErrorKind::UnexpectedEof, "{}", error)
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_other_snapshot_version() {
+        let mut bytes = World::world_init(7).to_snapshot_bytes();
+        bytes[8..12].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        match World::from_snapshot_bytes(&bytes) {
+            Ok(_) => panic!("Snapshot with version {} loaded", SNAPSHOT_VERSION + 1),
+            Err(error) => assert!(error.kind() == ErrorKind::InvalidData, "{}", error)
+        }
+    }
+
+    #[test]
+    fn rejects_broken_branch_tree() {
+        let world = World::world_init(7);
+        let genome = PlantGenome::human_made_tree_genome();
+        let mut rng = WorldRng::seed_from_u64(7);
+
+        let mut unconnected = Plant::new(genome.clone(), 10.0, 10.0, 100.0, &world.terrain, &mut rng);
+        unconnected.branches.push(Branch::new(0.5, 0.5, 0.5, 0.5, &mut rng));
+        let mut no_branches = Plant::new(genome, 10.0, 10.0, 100.0, &world.terrain, &mut rng);
+        no_branches.branches.clear();
+
+        for plant in [unconnected, no_branches] {
+            let mut w = SnapshotWriter::new();
+            write_plant(&mut w, &plant);
+            match read_plant(&mut SnapshotReader::new(&w.bytes)) {
+                Ok(_) => panic!("Plant with a broken branch tree loaded"),
+                Err(error) => assert!(error.kind() == ErrorKind::InvalidData, "{}", error)
+            }
+        }
+    }
+}
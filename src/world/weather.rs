@@ -0,0 +1,142 @@
+use rand::Rng;
+
+use crate::plant::plant_option_vec::PlantOptionVec;
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+use crate::world::WorldRng;
+
+// Weather events come and go over patches of the terrain grid: rain soaks the soil, droughts dry it out for a while
+// and storms snap off branches too weak to hold up their own length in the wind.
+
+pub const WEATHER_TICK_MOD: u64 = 30;           // Weather gets updated once every this many world ticks
+const MAX_WEATHER_EVENTS: usize = 8;
+const WEATHER_EVENT_CHANCE: f32 = 0.05;         // Chance of a new event starting each update
+
+// All per world tick
+const RAIN_MOISTURE: f32 = 0.002;               // Soil moisture added in the middle of a full strength shower
+const DROUGHT_RECOVERY: f32 = 0.0005;           // Fraction of a node's extra drought dryness that fades away
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WeatherKind {
+    Rain,
+    Drought,
+    Storm
+}
+
+pub struct WeatherEvent {
+    pub kind: WeatherKind,
+    pub x: f32,                 // Centre, in terrain cells
+    pub z: f32,
+    pub radius: f32,            // In terrain cells
+    pub strength: f32,          // 0 to 1
+    pub ticks_remaining: u64
+}
+
+pub struct Weather {
+    pub events: Vec<WeatherEvent>
+}
+
+impl WeatherEvent {
+    pub fn random(rng: &mut WorldRng) -> WeatherEvent {
+        let kind = match rng.random_range(0..4) {
+            0 => WeatherKind::Drought,
+            1 => WeatherKind::Storm,
+            _ => WeatherKind::Rain
+        };
+
+        let ticks_remaining = match kind {
+            WeatherKind::Rain => rng.random_range(300 .. 1500),
+            WeatherKind::Drought => rng.random_range(3000 .. 12000),
+            WeatherKind::Storm => rng.random_range(100 .. 400)
+        };
+
+        return WeatherEvent {
+            kind,
+            x: rng.random_range(0.0 .. TERRAIN_GRID_ROWS as f32),
+            z: rng.random_range(0.0 .. TERRAIN_GRID_ROWS as f32),
+            radius: rng.random_range(5.0 .. 25.0),
+            strength: rng.random_range(0.2 .. 1.0),
+            ticks_remaining
+        }
+    }
+
+    pub fn strength_at(&self, x: f32, z: f32) -> f32 {
+        //Full strength in the middle, fading out to nothing at the edge. Distances wrap around the world edges like the terrain does
+        let rows = TERRAIN_GRID_ROWS as f32;
+        let x_dist = (x - self.x).rem_euclid(rows).min((self.x - x).rem_euclid(rows));
+        let z_dist = (z - self.z).rem_euclid(rows).min((self.z - z).rem_euclid(rows));
+        let dist = (x_dist * x_dist + z_dist * z_dist).sqrt();
+
+        return self.strength * (1.0 - dist / self.radius).max(0.0);
+    }
+}
+
+impl Weather {
+    pub fn new() -> Weather {
+        return Weather {
+            events: Vec::new()
+        }
+    }
+
+    pub fn tick(&mut self, terrain: &mut Terrain, plants: &mut PlantOptionVec, rng: &mut WorldRng) {
+        let ticks = WEATHER_TICK_MOD as f32;
+
+        if self.events.len() < MAX_WEATHER_EVENTS && rng.random_range(0.0 .. 1.0) < WEATHER_EVENT_CHANCE {
+            self.events.push(WeatherEvent::random(rng));
+        }
+
+        for row in terrain.grid.iter_mut() {
+            for grid_node in row.iter_mut() {
+                grid_node.drought -= grid_node.drought * (ticks * DROUGHT_RECOVERY).min(1.0);
+            }
+        }
+
+        for event in &self.events {
+            if event.kind == WeatherKind::Storm {
+                continue;
+            }
+
+            //Only the nodes within the event's radius, wrapping around the world edges
+            let reach = event.radius.ceil() as isize;
+            for x_offset in -reach ..= reach {
+                for z_offset in -reach ..= reach {
+                    let x = (event.x.round() as isize + x_offset).rem_euclid(TERRAIN_GRID_ROWS as isize) as usize;
+                    let z = (event.z.round() as isize + z_offset).rem_euclid(TERRAIN_GRID_ROWS as isize) as usize;
+
+                    let strength = event.strength_at(x as f32, z as f32);
+                    if strength <= 0.0 {
+                        continue;
+                    }
+
+                    let grid_node = &mut terrain.grid[x][z];
+                    match event.kind {
+                        WeatherKind::Rain => {
+                            grid_node.soil_moisture = (grid_node.soil_moisture + ticks * RAIN_MOISTURE * strength).min(1.0);
+                        },
+                        WeatherKind::Drought => {
+                            grid_node.drought = grid_node.drought.max(strength);
+                        },
+                        WeatherKind::Storm => ()
+                    }
+                }
+            }
+        }
+
+        for event in &self.events {
+            if event.kind != WeatherKind::Storm {
+                continue;
+            }
+
+            for plant in plants.internal_vec.iter_mut().flatten() {
+                let wind = event.strength_at(plant.root_position.0, plant.root_position.2);
+                if wind > 0.0 {
+                    plant.break_in_wind(wind, terrain, rng);
+                }
+            }
+        }
+
+        for event in &mut self.events {
+            event.ticks_remaining = event.ticks_remaining.saturating_sub(WEATHER_TICK_MOD);
+        }
+        self.events.retain(|event| event.ticks_remaining > 0);
+    }
+}
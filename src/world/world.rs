@@ -1,26 +1,99 @@
 use glium::DrawParameters;
 
 use crate::render::camera::CameraState;
+use crate::plant::Plant;
+use crate::plant::genome::PlantGenome;
 use crate::plant::plant_option_vec::PlantOptionVec;
-use crate::terrain::Terrain;
+use crate::terrain::{Terrain, TERRAIN_GRID_ROWS};
+use crate::terrain::soil_moisture::SOIL_MOISTURE_TICK_MOD;
+use crate::terrain::nutrients::NUTRIENT_TICK_MOD;
+use crate::world::{WorldRng, LightGrid, WorldClock};
+use crate::world::weather::{Weather, WEATHER_TICK_MOD};
+use crate::herbivore::herbivore_vec::{HerbivoreVec, HERBIVORE_TICK_MOD};
+
+use rand::Rng;
 
 pub struct World {
+    pub seed: u64,
+    pub rng: WorldRng,
+    pub total_ticks: u64,
+    pub clock: WorldClock,
+    pub weather: Weather,
+
     pub terrain: Terrain,
-    pub plants: PlantOptionVec
+    pub light_grid: LightGrid,
+    pub plants: PlantOptionVec,
+    pub herbivores: HerbivoreVec
 }
 
 impl World {
-    pub fn world_init() -> World {
+    pub fn world_init(seed: u64) -> World {
+        //The same seed (and the same inputs afterwards) always plays out the same simulation
+        let mut rng = WorldRng::seed_from_u64(seed);
+
         return World {
-            terrain: Terrain::random(),
-            plants: PlantOptionVec::new()
+            seed,
+            terrain: Terrain::random(&mut rng),
+            light_grid: LightGrid::empty(),
+            plants: PlantOptionVec::new(),
+            herbivores: HerbivoreVec::new(),
+            rng,
+            total_ticks: 0,
+            clock: WorldClock::new(),
+            weather: Weather::new()
         };
     }
 
-    pub fn tick(&mut self, total_ticks: u64, display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>, camera: &CameraState) {
-        self.terrain.water_height = (total_ticks as f32 * 0.1).sin() as f32 * 5.0 + (total_ticks as f32 * 0.0271).sin() as f32 * 5.0;
+    pub fn tick(&mut self, viewer_position: Option<(f32, f32, f32)>) {
+        self.total_ticks += 1;
+
+        self.terrain.water_height = (self.total_ticks as f32 * 0.1).sin() * 5.0 + (self.total_ticks as f32 * 0.0271).sin() * 5.0;
+
+        if self.total_ticks.is_multiple_of(SOIL_MOISTURE_TICK_MOD) {
+            self.terrain.tick_soil_moisture();
+        }
+        if self.total_ticks.is_multiple_of(NUTRIENT_TICK_MOD) {
+            self.terrain.tick_nutrients();
+        }
+        if self.total_ticks.is_multiple_of(WEATHER_TICK_MOD) {
+            self.weather.tick(&mut self.terrain, &mut self.plants, &mut self.rng);
+        }
+
+        let sun = self.clock.sun_state(self.total_ticks);
+        self.plants.tick(&mut self.terrain, &sun, &mut self.light_grid, self.total_ticks, viewer_position, &mut self.rng);
+
+        if self.total_ticks.is_multiple_of(HERBIVORE_TICK_MOD) {
+            self.herbivores.tick(&mut self.terrain, &mut self.plants, &mut self.light_grid, self.total_ticks, &mut self.rng);
+        }
+    }
+
+    pub fn spawn_random_plants(&mut self, count: usize, genome: &PlantGenome) {
+        //Attempts to place count plants at random spots, skipping any that land underwater
+        for _i in 0..count {
+            let (x, z) = (self.rng.random_range(0.0 .. TERRAIN_GRID_ROWS as f32), self.rng.random_range(0.0 .. TERRAIN_GRID_ROWS as f32));
+
+            if self.terrain.get_height(x, z) < self.terrain.water_height {
+                continue;
+            }
+
+            let new_plant = Plant::new(
+                genome.clone(), 
+                x,
+                z,
+                100.0, 
+                &self.terrain,
+                &mut self.rng
+            );
+            self.plants.add_plant(new_plant);
+        }
+    }
+
+    pub fn spawn_random_herbivores(&mut self, count: usize) {
+        self.herbivores.spawn_random_herbivores(count, &self.terrain, &mut self.rng);
+    }
 
-        self.plants.tick(&self.terrain, total_ticks, display, camera);
+    pub fn upload_models(&mut self, display: &glium::backend::glutin::Display<glium::glutin::surface::WindowSurface>) {
+        self.plants.upload_models(display);
     }
 
     pub fn render(
@@ -33,6 +106,7 @@ impl World {
         params: &DrawParameters
     ) {
         self.plants.render(total_time, target, program, display, camera, params);
+        self.herbivores.render(&self.terrain, target, program, display, camera, params);
 
         self.terrain.render(target, program, display, camera, params);
     }
@@ -0,0 +1,98 @@
+use std::f32::consts::PI;
+
+// Turns the world tick count into the time of day and year, and from that where the sun is in the sky.
+// Coordinates for sun directions: x is east, y is up, z is north.
+
+const AXIAL_TILT: f32 = 23.44 * PI / 180.0;
+
+pub struct WorldClock {
+    pub day_length_ticks: u64,
+    pub year_length_days: u64,
+    pub latitude: f32               // In degrees, negative is the southern hemisphere
+}
+
+// Everything plants need to know about the sun for one tick
+pub struct SunState {
+    pub direction: (f32, f32, f32), // Unit vector pointing towards the sun
+    pub intensity: f32,             // 0 at night, averages out to about 1 over a year
+    pub season: f32                 // 0 spring, 1 summer, 2 autumn, 3 winter, in between values blend from one to the next
+}
+
+impl WorldClock {
+    pub fn new() -> WorldClock {
+        // A day lasts a minute and a year lasts 24 days at 20 ticks per second
+        return WorldClock {
+            day_length_ticks: 1200,
+            year_length_days: 24,
+            latitude: 45.0
+        }
+    }
+
+    pub fn time_of_day(&self, total_ticks: u64) -> f32 {
+        //0 is midnight, 0.5 is noon. Worlds start at sunrise
+        let day_length = self.day_length_ticks.max(1);
+        return ((total_ticks + day_length / 4) % day_length) as f32 / day_length as f32;
+    }
+
+    pub fn time_of_year(&self, total_ticks: u64) -> f32 {
+        //0 is the spring equinox of the northern hemisphere
+        let year_length = self.day_length_ticks.max(1) * self.year_length_days.max(1);
+        return (total_ticks % year_length) as f32 / year_length as f32;
+    }
+
+    pub fn day(&self, total_ticks: u64) -> u64 {
+        return total_ticks / self.day_length_ticks.max(1);
+    }
+
+    pub fn season(&self, total_ticks: u64) -> f32 {
+        let season = self.time_of_year(total_ticks) * 4.0;
+        if self.latitude < 0.0 {
+            return (season + 2.0) % 4.0;
+        }
+        return season;
+    }
+
+    pub fn sun_direction(&self, total_ticks: u64) -> (f32, f32, f32) {
+        let latitude = self.latitude.clamp(-89.0, 89.0).to_radians();
+        let declination = AXIAL_TILT * (2.0 * PI * self.time_of_year(total_ticks)).sin();
+        let hour_angle = 2.0 * PI * (self.time_of_day(total_ticks) - 0.5);
+
+        return (
+            -declination.cos() * hour_angle.sin(),
+            latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos(),
+            latitude.cos() * declination.sin() - latitude.sin() * declination.cos() * hour_angle.cos()
+        );
+    }
+
+    pub fn sun_intensity(&self, total_ticks: u64) -> f32 {
+        //Scaled so that a day at the equinox averages out to 1 wherever the world is, and summer days make up for winter ones
+        let latitude = self.latitude.clamp(-80.0, 80.0).to_radians();
+        return self.sun_direction(total_ticks).1.max(0.0) * PI / latitude.cos();
+    }
+
+    pub fn sun_state(&self, total_ticks: u64) -> SunState {
+        return SunState {
+            direction: self.sun_direction(total_ticks),
+            intensity: self.sun_intensity(total_ticks),
+            season: self.season(total_ticks)
+        }
+    }
+
+    pub fn sky_color(&self, total_ticks: u64) -> (f32, f32, f32) {
+        //Blends from the night sky to the day sky as the sun comes up, going orange while it's close to the horizon
+        const NIGHT: (f32, f32, f32) = (0.02, 0.03, 0.08);
+        const DAY: (f32, f32, f32) = (0.60, 0.75, 0.95);
+        const SUNSET: (f32, f32, f32) = (0.90, 0.50, 0.30);
+
+        let sun_height = self.sun_direction(total_ticks).1;
+        let daylight = ((sun_height + 0.1) / 0.3).clamp(0.0, 1.0);
+        let sunset = (1.0 - (sun_height.abs() / 0.15)).clamp(0.0, 1.0) * 0.6;
+
+        let blend = |night: f32, day: f32, sunset_color: f32| -> f32 {
+            let sky = night + (day - night) * daylight;
+            return sky + (sunset_color - sky) * sunset;
+        };
+
+        return (blend(NIGHT.0, DAY.0, SUNSET.0), blend(NIGHT.1, DAY.1, SUNSET.1), blend(NIGHT.2, DAY.2, SUNSET.2));
+    }
+}
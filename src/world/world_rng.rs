@@ -0,0 +1,58 @@
+use rand::RngCore;
+
+// xoshiro256** with its state out in the open, so a run can be reproduced from a seed and resumed from a saved state.
+// Every random decision in the simulation goes through the one of these owned by the World.
+#[derive(Clone)]
+pub struct WorldRng {
+    state: [u64; 4]
+}
+
+fn splitmix64(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    return z ^ (z >> 31);
+}
+
+impl WorldRng {
+    pub fn seed_from_u64(seed: u64) -> WorldRng {
+        let mut x = seed;
+        return WorldRng {
+            state: [splitmix64(&mut x), splitmix64(&mut x), splitmix64(&mut x), splitmix64(&mut x)]
+        };
+    }
+
+    pub fn from_state(state: [u64; 4]) -> WorldRng {
+        return WorldRng { state };
+    }
+
+    pub fn state(&self) -> [u64; 4] {
+        return self.state;
+    }
+}
+
+impl RngCore for WorldRng {
+    fn next_u32(&mut self) -> u32 {
+        return (self.next_u64() >> 32) as u32;
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        return result;
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand::rand_core::impls::fill_bytes_via_next(self, dst);
+    }
+}